@@ -0,0 +1,138 @@
+// Copyright 2020-2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::sync::RwLock;
+
+use crate::Error;
+use crate::Result;
+
+/// Implementation of base64url encoding without padding, as used for disclosures, digests, salts and JWT
+/// segments.
+///
+/// The crate uses [`DefaultBase64Engine`] unless another engine is installed with [`set_base64_engine`],
+/// e.g. a SIMD-accelerated one or one that rejects inputs the default engine tolerates.
+pub trait Base64Engine: Sync + Send {
+  /// Encodes `input` as base64url without padding.
+  fn encode(&self, input: &[u8]) -> String;
+
+  /// Decodes base64url-encoded `input`.
+  ///
+  /// ## Error
+  /// Returns [`Error::DeserializationError`] if `input` is not valid base64url.
+  fn decode(&self, input: &str) -> Result<Vec<u8>>;
+}
+
+/// The default [`Base64Engine`], based on `multibase`.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct DefaultBase64Engine;
+
+impl Base64Engine for DefaultBase64Engine {
+  fn encode(&self, input: &[u8]) -> String {
+    multibase::Base::Base64Url.encode(input)
+  }
+
+  fn decode(&self, input: &str) -> Result<Vec<u8>> {
+    multibase::Base::Base64Url
+      .decode(input)
+      .map_err(|err| Error::DeserializationError(err.to_string()))
+  }
+}
+
+static ENGINE: RwLock<Option<&'static dyn Base64Engine>> = RwLock::new(None);
+
+/// Installs `engine` for all base64url encoding and decoding of the crate, replacing the previously
+/// installed engine.
+///
+/// ## Note
+///
+/// The engine is global to the process and affects every user of the crate in it. It should be installed
+/// once at startup by the application, libraries depending on this crate must not install engines. The default
+/// engine can be restored with `set_base64_engine(&DefaultBase64Engine)`.
+pub fn set_base64_engine(engine: &'static dyn Base64Engine) {
+  *ENGINE.write().unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(engine);
+}
+
+fn engine() -> &'static dyn Base64Engine {
+  ENGINE
+    .read()
+    .unwrap_or_else(|poisoned| poisoned.into_inner())
+    .unwrap_or(&DefaultBase64Engine)
+}
+
+/// Encodes `input` with the installed [`Base64Engine`].
+pub(crate) fn encode(input: impl AsRef<[u8]>) -> String {
+  engine().encode(input.as_ref())
+}
+
+/// Decodes `input` with the installed [`Base64Engine`].
+pub(crate) fn decode(input: &str) -> Result<Vec<u8>> {
+  engine().decode(input)
+}
+
+#[cfg(test)]
+mod test {
+  use super::set_base64_engine;
+  use super::Base64Engine;
+  use super::DefaultBase64Engine;
+  use crate::Disclosure;
+  use crate::Result;
+  use std::sync::atomic::AtomicUsize;
+  use std::sync::atomic::Ordering;
+  use std::sync::Mutex;
+  use std::sync::MutexGuard;
+
+  /// Behaves like the default engine, so installing it does not affect other tests.
+  struct CountingEngine(AtomicUsize);
+
+  impl Base64Engine for CountingEngine {
+    fn encode(&self, input: &[u8]) -> String {
+      self.0.fetch_add(1, Ordering::SeqCst);
+      DefaultBase64Engine.encode(input)
+    }
+
+    fn decode(&self, input: &str) -> Result<Vec<u8>> {
+      self.0.fetch_add(1, Ordering::SeqCst);
+      DefaultBase64Engine.decode(input)
+    }
+  }
+
+  static COUNTING: CountingEngine = CountingEngine(AtomicUsize::new(0));
+
+  /// Serializes the tests installing engines.
+  static ENGINE_LOCK: Mutex<()> = Mutex::new(());
+
+  /// Installs an engine until it is dropped, restoring the default engine even if the test panics.
+  struct InstalledEngine {
+    _guard: MutexGuard<'static, ()>,
+  }
+
+  impl InstalledEngine {
+    fn install(engine: &'static dyn Base64Engine) -> Self {
+      let guard = ENGINE_LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+      set_base64_engine(engine);
+      Self { _guard: guard }
+    }
+  }
+
+  impl Drop for InstalledEngine {
+    fn drop(&mut self) {
+      set_base64_engine(&DefaultBase64Engine);
+    }
+  }
+
+  #[test]
+  fn custom_engine() {
+    let _installed = InstalledEngine::install(&COUNTING);
+    let disclosure = Disclosure::new("lklxF5jMYlGTPUovMNIvCA".to_owned(), None, "US".into()).unwrap();
+    assert_eq!(disclosure.as_str(), "WyJsa2x4RjVqTVlsR1RQVW92TU5JdkNBIiwgIlVTIl0");
+    Disclosure::parse(disclosure.into_string()).unwrap();
+    assert!(COUNTING.0.load(Ordering::SeqCst) >= 2);
+  }
+
+  #[test]
+  fn default_engine() {
+    assert_eq!(DefaultBase64Engine.encode(b"{}"), "e30");
+    assert_eq!(DefaultBase64Engine.decode("e30").unwrap(), b"{}");
+    assert!(DefaultBase64Engine.decode("e30=!").is_err());
+  }
+}
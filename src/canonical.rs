@@ -0,0 +1,168 @@
+// Copyright 2020-2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use serde_json::Number;
+use serde_json::Value;
+
+/// Serializes `value` using the [JSON Canonicalization Scheme](https://www.rfc-editor.org/rfc/rfc8785) (JCS).
+///
+/// Object members are sorted by the UTF-16 code units of their names, no whitespace is emitted and numbers
+/// are formatted like ECMAScript's `Number.prototype.toString`, so equal values always serialize to the
+/// same bytes.
+///
+/// ## Note
+///
+/// Like in ECMAScript, every number is formatted as an `f64`, so integers above 2^53 are rounded, e.g.
+/// [`u64::MAX`] becomes `18446744073709552000`.
+///
+/// With the `arbitrary_precision` feature enabled, numbers that are out of the range of `f64` can not be
+/// canonicalized and are written as they are.
+pub fn canonicalize(value: &Value) -> String {
+  let mut output = String::new();
+  write_canonical(value, &mut output);
+  output
+}
+
+fn write_canonical(value: &Value, output: &mut String) {
+  match value {
+    // `serde_json` escapes strings the same way as JCS.
+    Value::Null | Value::Bool(_) | Value::String(_) => output.push_str(&value.to_string()),
+    Value::Number(number) => output.push_str(&canonical_number(number)),
+    Value::Array(elements) => {
+      output.push('[');
+      for (index, element) in elements.iter().enumerate() {
+        if index > 0 {
+          output.push(',');
+        }
+        write_canonical(element, output);
+      }
+      output.push(']');
+    }
+    Value::Object(object) => {
+      let mut members: Vec<(&String, &Value)> = object.iter().collect();
+      members.sort_by(|(a, _), (b, _)| a.encode_utf16().cmp(b.encode_utf16()));
+      output.push('{');
+      for (index, (name, value)) in members.into_iter().enumerate() {
+        if index > 0 {
+          output.push(',');
+        }
+        output.push_str(&Value::String(name.clone()).to_string());
+        output.push(':');
+        write_canonical(value, output);
+      }
+      output.push('}');
+    }
+  }
+}
+
+fn canonical_number(number: &Number) -> String {
+  let value = match number.as_f64() {
+    Some(value) if value.is_finite() => value,
+    _ => return number.to_string(),
+  };
+  if value == 0.0 {
+    // Also covers `-0`.
+    return "0".to_owned();
+  }
+
+  // The shortest digits that round-trip and the decimal exponent, e.g. `1.25e3` for `1250`.
+  let scientific = format!("{:e}", value.abs());
+  let (mantissa, exponent) = scientific.split_once('e').unwrap_or((&scientific, "0"));
+  let digits: String = mantissa.chars().filter(|c| *c != '.').collect();
+  let digit_count = digits.len() as i32;
+  // Position of the decimal point relative to the first digit.
+  let point = exponent.parse::<i32>().unwrap_or_default() + 1;
+
+  let formatted = if digit_count <= point && point <= 21 {
+    format!("{}{}", digits, "0".repeat((point - digit_count) as usize))
+  } else if 0 < point && point <= 21 {
+    let (integer, fraction) = digits.split_at(point as usize);
+    format!("{}.{}", integer, fraction)
+  } else if -6 < point && point <= 0 {
+    format!("0.{}{}", "0".repeat(-point as usize), digits)
+  } else {
+    let (first, rest) = digits.split_at(1);
+    let exponent = point - 1;
+    let sign = if exponent < 0 { '-' } else { '+' };
+    if rest.is_empty() {
+      format!("{}e{}{}", first, sign, exponent.abs())
+    } else {
+      format!("{}.{}e{}{}", first, rest, sign, exponent.abs())
+    }
+  };
+
+  if value < 0.0 {
+    format!("-{}", formatted)
+  } else {
+    formatted
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::canonicalize;
+  use serde_json::json;
+  use serde_json::Value;
+
+  #[test]
+  fn numbers() {
+    // Examples of RFC 8785, appendix B, that are finite.
+    let cases = [
+      ("0", "0"),
+      ("-0", "0"),
+      ("1e30", "1e+30"),
+      ("4.50", "4.5"),
+      ("2e-3", "0.002"),
+      ("0.000001", "0.000001"),
+      ("1e-7", "1e-7"),
+      ("333333333.33333329", "333333333.3333333"),
+      ("295147905179352830000", "295147905179352830000"),
+      ("1e21", "1e+21"),
+      ("-5e-324", "-5e-324"),
+      ("1.7976931348623157e308", "1.7976931348623157e+308"),
+      ("9007199254740992", "9007199254740992"),
+      ("-1.5e-10", "-1.5e-10"),
+    ];
+    for (input, expected) in cases {
+      let value = Value::from(input.parse::<f64>().unwrap());
+      assert_eq!(canonicalize(&value), expected, "{}", input);
+    }
+  }
+
+  #[test]
+  fn large_integers() {
+    // Rounded to the nearest `f64`, with and without `arbitrary_precision`.
+    assert_eq!(canonicalize(&json!(u64::MAX)), "18446744073709552000");
+    assert_eq!(canonicalize(&json!(i64::MIN)), "-9223372036854776000");
+    assert_eq!(canonicalize(&json!(9007199254740993_u64)), "9007199254740992");
+  }
+
+  #[test]
+  fn member_order() {
+    // Example of RFC 8785, section 3.2.3.
+    let value: Value = serde_json::from_str(
+      r#"{
+        "\u20ac": "Euro Sign",
+        "\r": "Carriage Return",
+        "\ufb33": "Hebrew Letter Dalet With Dagesh",
+        "1": "One",
+        "\ud83d\ude00": "Emoji: Grinning Face",
+        "\u0080": "Control",
+        "\u00f6": "Latin Small Letter O With Diaeresis"
+      }"#,
+    )
+    .unwrap();
+    assert_eq!(
+      canonicalize(&value),
+      "{\"\\r\":\"Carriage Return\",\"1\":\"One\",\"\u{80}\":\"Control\",\"\u{f6}\":\"Latin Small Letter O With \
+       Diaeresis\",\"\u{20ac}\":\"Euro Sign\",\"\u{1f600}\":\"Emoji: Grinning Face\",\"\u{fb33}\":\"Hebrew Letter \
+       Dalet With Dagesh\"}"
+    );
+  }
+
+  #[test]
+  fn nested() {
+    let value = json!({ "b": [true, null, { "d": 1.0, "c": "\u{1}" }], "a": {} });
+    assert_eq!(canonicalize(&value), r#"{"a":{},"b":[true,null,{"c":"\u0001","d":1}]}"#);
+  }
+}
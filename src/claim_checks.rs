@@ -0,0 +1,121 @@
+// Copyright 2020-2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use serde::Deserialize;
+use serde::Serialize;
+use serde_json::Map;
+use serde_json::Value;
+
+use crate::pointer::join_pointer;
+
+/// A registered claim of an SD-JWT payload that is incoherent, see [`validate_claims`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClaimViolation {
+  /// JSON pointer to the offending claim.
+  pub path: String,
+  /// Description of the violation.
+  pub reason: String,
+}
+
+/// Checks the registered claims of the SD-JWT payload `object` for basic coherence before it is signed and
+/// returns all violations, empty if there are none:
+/// * `iat`, `nbf` and `exp` are numbers, `exp` is after `iat` and not before `nbf`.
+/// * `iss` is a URI.
+/// * `cnf` is an object, and is present if `key_binding_required`.
+///
+/// Only claims that are not selectively disclosable are checked, see
+/// [`SdObjectEncoder::validate_claims`](crate::SdObjectEncoder::validate_claims).
+pub fn validate_claims(object: &Map<String, Value>, key_binding_required: bool) -> Vec<ClaimViolation> {
+  let mut violations = vec![];
+  let mut violation = |claim: &str, reason: String| {
+    violations.push(ClaimViolation {
+      path: join_pointer("", claim),
+      reason,
+    })
+  };
+
+  let mut time = |claim: &str| match object.get(claim) {
+    None => None,
+    Some(value) => {
+      let time = value.as_f64();
+      if time.is_none() {
+        violation(claim, "expected a NumericDate".to_string());
+      }
+      time
+    }
+  };
+  let (iat, nbf, exp) = (time("iat"), time("nbf"), time("exp"));
+  if let (Some(iat), Some(exp)) = (iat, exp) {
+    if exp <= iat {
+      violation(
+        "exp",
+        format!("expires at {} which is not after it was issued at {}", exp, iat),
+      );
+    }
+  }
+  if let (Some(nbf), Some(exp)) = (nbf, exp) {
+    if exp < nbf {
+      violation(
+        "exp",
+        format!("expires at {} which is before it becomes valid at {}", exp, nbf),
+      );
+    }
+  }
+
+  if let Some(iss) = object.get("iss") {
+    if !iss.as_str().map_or(false, is_uri) {
+      violation("iss", "expected a URI".to_string());
+    }
+  }
+
+  match object.get("cnf") {
+    Some(cnf) if !cnf.is_object() => violation("cnf", "expected an object".to_string()),
+    None if key_binding_required => violation("cnf", "key binding is required but `cnf` is missing".to_string()),
+    _ => (),
+  }
+
+  violations
+}
+
+/// Returns whether `value` starts with a URI scheme as defined in
+/// [RFC 3986](https://www.rfc-editor.org/rfc/rfc3986#section-3.1), followed by a non-empty remainder without
+/// whitespace.
+fn is_uri(value: &str) -> bool {
+  let Some((scheme, rest)) = value.split_once(':') else {
+    return false;
+  };
+  let mut chars = scheme.chars();
+  chars.next().map_or(false, |first| first.is_ascii_alphabetic())
+    && chars.all(|char| char.is_ascii_alphanumeric() || matches!(char, '+' | '-' | '.'))
+    && !rest.is_empty()
+    && !rest.chars().any(char::is_whitespace)
+}
+
+#[cfg(test)]
+mod test {
+  use super::validate_claims;
+  use serde_json::json;
+
+  #[test]
+  fn violations() {
+    let object = json!({
+      "iss": "https://issuer.example.com",
+      "iat": 1683000000,
+      "nbf": 1683000000,
+      "exp": 1883000000,
+      "cnf": { "jkt": "NzbLsXh8uDCcd-6MNwXF4W_7noWXFZAfHkxZsRGC9Xs" }
+    });
+    assert!(validate_claims(object.as_object().unwrap(), true).is_empty());
+
+    let object = json!({
+      "iss": "issuer example",
+      "iat": 1683000000,
+      "nbf": "now",
+      "exp": 1683000000
+    });
+    let violations = validate_claims(object.as_object().unwrap(), true);
+    let paths: Vec<&str> = violations.iter().map(|violation| violation.path.as_str()).collect();
+    assert_eq!(paths, vec!["/nbf", "/exp", "/iss", "/cnf"]);
+    assert!(validate_claims(&serde_json::Map::new(), false).is_empty());
+  }
+}
@@ -0,0 +1,183 @@
+// Copyright 2020-2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::fmt::Display;
+use std::str::FromStr;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::pointer::join_pointer;
+use crate::pointer::pointer_segments;
+use crate::pointer::segments_match;
+use crate::Error;
+use crate::Result;
+
+/// The path to a claim, represented as a [JSON pointer](https://datatracker.ietf.org/doc/html/rfc6901).
+///
+/// Paths are built from unescaped segments, so claim names containing `/` or `~` need no manual escaping,
+/// and can be passed wherever a JSON pointer is expected using [`ClaimPath::as_str`].
+///
+/// ## Example
+/// ```
+/// use sd_jwt_payload::json;
+/// use sd_jwt_payload::ClaimPath;
+/// use sd_jwt_payload::SdObjectEncoder;
+///
+/// let path = ClaimPath::root().key("address").key("street/number");
+/// assert_eq!(path.as_str(), "/address/street~1number");
+/// assert_eq!(ClaimPath::root().key("nationalities").index(1), "/nationalities/1".parse::<ClaimPath>().unwrap());
+///
+/// let mut encoder = SdObjectEncoder::try_from(json!({ "address": { "street/number": "Schulstr. 12" } })).unwrap();
+/// encoder.conceal(path.as_str(), None).unwrap();
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct ClaimPath {
+  /// Always a valid JSON pointer.
+  pointer: String,
+}
+
+impl ClaimPath {
+  /// Returns the path of the top level object.
+  pub fn root() -> Self {
+    Self::default()
+  }
+
+  /// Returns the path to the property `name` of the value at this path.
+  pub fn key(mut self, name: &str) -> Self {
+    self.push(name);
+    self
+  }
+
+  /// Returns the path to the element at `index` of the array at this path.
+  pub fn index(self, index: usize) -> Self {
+    self.key(&index.to_string())
+  }
+
+  /// Appends the unescaped `segment` to this path.
+  pub fn push(&mut self, segment: &str) {
+    self.pointer = join_pointer(&self.pointer, segment);
+  }
+
+  /// Returns the path of the parent value, `None` for the root.
+  pub fn parent(&self) -> Option<Self> {
+    // Escaped segments do not contain `/`.
+    self.pointer.rfind('/').map(|index| Self {
+      pointer: self.pointer[..index].to_owned(),
+    })
+  }
+
+  /// Returns the unescaped segments of this path.
+  pub fn segments(&self) -> Vec<String> {
+    // The pointer is valid by construction.
+    pointer_segments(&self.pointer).unwrap_or_default()
+  }
+
+  /// Returns whether this path is `other` or a path into the value at `other`.
+  pub fn starts_with(&self, other: &ClaimPath) -> bool {
+    self.segments().starts_with(&other.segments())
+  }
+
+  /// Returns whether this path matches `pattern`, in which a `*` segment matches any single segment.
+  pub fn matches(&self, pattern: &ClaimPath) -> bool {
+    segments_match(&pattern.segments(), &self.segments())
+  }
+
+  /// Returns the path as JSON pointer.
+  pub fn as_str(&self) -> &str {
+    &self.pointer
+  }
+}
+
+impl Display for ClaimPath {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.write_str(&self.pointer)
+  }
+}
+
+impl FromStr for ClaimPath {
+  type Err = Error;
+
+  /// Parses a JSON pointer.
+  ///
+  /// ## Error
+  /// Returns [`Error::InvalidPath`] if `pointer` is not a valid JSON pointer.
+  fn from_str(pointer: &str) -> Result<Self> {
+    pointer_segments(pointer)?;
+    Ok(Self {
+      pointer: pointer.to_owned(),
+    })
+  }
+}
+
+impl TryFrom<String> for ClaimPath {
+  type Error = Error;
+
+  fn try_from(pointer: String) -> Result<Self> {
+    pointer_segments(&pointer)?;
+    Ok(Self { pointer })
+  }
+}
+
+impl From<ClaimPath> for String {
+  fn from(path: ClaimPath) -> Self {
+    path.pointer
+  }
+}
+
+impl AsRef<str> for ClaimPath {
+  fn as_ref(&self) -> &str {
+    &self.pointer
+  }
+}
+
+impl PartialEq<str> for ClaimPath {
+  fn eq(&self, other: &str) -> bool {
+    self.pointer == other
+  }
+}
+
+impl PartialEq<&str> for ClaimPath {
+  fn eq(&self, other: &&str) -> bool {
+    self.pointer == *other
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::ClaimPath;
+  use crate::Error;
+  use serde_json::json;
+
+  #[test]
+  fn construction() {
+    let path = ClaimPath::root().key("address").key("a~b");
+    assert_eq!(path, "/address/a~0b");
+    assert_eq!(path.segments(), vec!["address", "a~b"]);
+    assert_eq!(path.parent().unwrap(), ClaimPath::root().key("address"));
+    assert_eq!(ClaimPath::root().parent(), None);
+    assert!(path.starts_with(&ClaimPath::root().key("address")));
+    assert!(!ClaimPath::root()
+      .key("addresses")
+      .starts_with(&ClaimPath::root().key("address")));
+    assert!(ClaimPath::root()
+      .key("nationalities")
+      .index(0)
+      .matches(&"/nationalities/*".parse().unwrap()));
+  }
+
+  #[test]
+  fn parse_and_serde() {
+    let path: ClaimPath = "/a~1b/0".parse().unwrap();
+    assert_eq!(path.segments(), vec!["a/b", "0"]);
+    assert_eq!(path.to_string(), "/a~1b/0");
+    assert!(matches!(
+      "a".parse::<ClaimPath>().unwrap_err(),
+      Error::InvalidPath { .. }
+    ));
+    assert_eq!(serde_json::to_value(&path).unwrap(), json!("/a~1b/0"));
+    assert_eq!(serde_json::from_value::<ClaimPath>(json!("/a~1b/0")).unwrap(), path);
+    assert!(serde_json::from_value::<ClaimPath>(json!("/a~2")).is_err());
+  }
+}
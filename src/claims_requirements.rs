@@ -0,0 +1,174 @@
+// Copyright 2020-2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use serde_json::Map;
+use serde_json::Value;
+
+use crate::pointer::parse_pointer;
+use crate::Result;
+
+/// A constraint on a claim of a decoded SD-JWT, see [`ClaimsRequirements`].
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub enum ClaimConstraint {
+  /// The claim must be disclosed, with any value.
+  Present,
+  /// The claim must be disclosed with exactly this value.
+  Equals(Value),
+  /// The claim must be disclosed with one of these values.
+  OneOf(Vec<Value>),
+  /// The claim must be disclosed with a string value matching this regular expression.
+  #[cfg(feature = "regex")]
+  Matches(regex::Regex),
+}
+
+impl ClaimConstraint {
+  /// Returns the reason why `value` does not satisfy the constraint, `None` if it does.
+  fn violation(&self, value: Option<&Value>) -> Option<String> {
+    let Some(value) = value else {
+      return Some("claim is not disclosed".to_string());
+    };
+    match self {
+      Self::Present => None,
+      Self::Equals(expected) => (value != expected).then(|| "claim has another value".to_string()),
+      Self::OneOf(expected) => (!expected.contains(value)).then(|| "claim has none of the expected values".to_string()),
+      #[cfg(feature = "regex")]
+      Self::Matches(regex) => (!value.as_str().map_or(false, |value| regex.is_match(value)))
+        .then(|| format!("claim does not match `{}`", regex.as_str())),
+    }
+  }
+}
+
+/// Claims a relying party requires to be disclosed, checked against a decoded SD-JWT object.
+///
+/// ## Example
+/// ```
+/// use sd_jwt_payload::json;
+/// use sd_jwt_payload::ClaimConstraint;
+/// use sd_jwt_payload::ClaimsRequirements;
+///
+/// let mut requirements = ClaimsRequirements::new();
+/// requirements.require("/given_name", ClaimConstraint::Present).unwrap();
+/// requirements
+///   .require("/address/country", ClaimConstraint::OneOf(vec![json!("DE"), json!("AT")]))
+///   .unwrap();
+///
+/// let decoded = json!({ "given_name": "Erika", "address": { "country": "FR" } });
+/// let report = requirements.check(decoded.as_object().unwrap());
+/// assert!(!report.is_satisfied());
+/// assert_eq!(report.unsatisfied().next().unwrap().path, "/address/country");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ClaimsRequirements {
+  requirements: Vec<(String, ClaimConstraint)>,
+}
+
+impl ClaimsRequirements {
+  /// Creates [`ClaimsRequirements`] without any requirements.
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Requires the claim at the JSON pointer `path` to satisfy `constraint`.
+  ///
+  /// ## Error
+  /// Returns [`Error::InvalidPath`](crate::Error::InvalidPath) if `path` is not a valid JSON pointer.
+  pub fn require(&mut self, path: &str, constraint: ClaimConstraint) -> Result<()> {
+    parse_pointer(path)?;
+    self.requirements.push((path.to_owned(), constraint));
+    Ok(())
+  }
+
+  /// Checks every requirement against `decoded`, the object returned by
+  /// [`SdObjectDecoder::decode`](crate::SdObjectDecoder::decode), and reports each of them.
+  pub fn check(&self, decoded: &Map<String, Value>) -> RequirementsReport {
+    let object = Value::Object(decoded.clone());
+    let claims = self
+      .requirements
+      .iter()
+      .map(|(path, constraint)| {
+        // Paths are checked to be valid when the requirement is added.
+        let value = parse_pointer(path).ok().and_then(|pointer| pointer.get(&object).ok());
+        ClaimSatisfaction {
+          path: path.clone(),
+          violation: constraint.violation(value),
+        }
+      })
+      .collect();
+    RequirementsReport { claims }
+  }
+}
+
+/// Result of [`ClaimsRequirements::check`], in the order the requirements were added.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequirementsReport {
+  /// Satisfaction of every requirement.
+  pub claims: Vec<ClaimSatisfaction>,
+}
+
+impl RequirementsReport {
+  /// Returns whether all requirements are satisfied.
+  pub fn is_satisfied(&self) -> bool {
+    self.claims.iter().all(ClaimSatisfaction::is_satisfied)
+  }
+
+  /// Returns the requirements that are not satisfied.
+  pub fn unsatisfied(&self) -> impl Iterator<Item = &ClaimSatisfaction> {
+    self.claims.iter().filter(|claim| !claim.is_satisfied())
+  }
+}
+
+/// Satisfaction of a single requirement of [`ClaimsRequirements`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClaimSatisfaction {
+  /// JSON pointer to the required claim.
+  pub path: String,
+  /// Why the requirement is not satisfied, `None` if it is.
+  pub violation: Option<String>,
+}
+
+impl ClaimSatisfaction {
+  /// Returns whether the requirement is satisfied.
+  pub fn is_satisfied(&self) -> bool {
+    self.violation.is_none()
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::ClaimConstraint;
+  use super::ClaimsRequirements;
+  use serde_json::json;
+
+  #[test]
+  fn check() {
+    let mut requirements = ClaimsRequirements::new();
+    requirements.require("/given_name", ClaimConstraint::Present).unwrap();
+    requirements
+      .require("/age_over_18", ClaimConstraint::Equals(json!(true)))
+      .unwrap();
+    requirements
+      .require("/nationalities/0", ClaimConstraint::OneOf(vec![json!("DE")]))
+      .unwrap();
+    requirements.require("/email", ClaimConstraint::Present).unwrap();
+    assert!(requirements.require("email", ClaimConstraint::Present).is_err());
+
+    let decoded = json!({ "given_name": "Erika", "age_over_18": false, "nationalities": ["DE"] });
+    let report = requirements.check(decoded.as_object().unwrap());
+    let satisfied: Vec<bool> = report.claims.iter().map(|claim| claim.is_satisfied()).collect();
+    assert_eq!(satisfied, vec![true, false, true, false]);
+    assert_eq!(report.unsatisfied().count(), 2);
+  }
+
+  #[cfg(feature = "regex")]
+  #[test]
+  fn matches() {
+    let mut requirements = ClaimsRequirements::new();
+    let regex = regex::Regex::new("^[^@]+@example\\.com$").unwrap();
+    requirements.require("/email", ClaimConstraint::Matches(regex)).unwrap();
+    let report = requirements.check(json!({ "email": "erika@example.com" }).as_object().unwrap());
+    assert!(report.is_satisfied());
+    let report = requirements.check(json!({ "email": "erika@example.org" }).as_object().unwrap());
+    assert!(!report.is_satisfied());
+  }
+}
@@ -6,44 +6,69 @@ use crate::DIGESTS_KEY;
 use crate::SD_ALG;
 use crate::SHA_ALG_NAME;
 
+use super::digests_equal;
 use super::Disclosure;
 use super::Hasher;
-#[cfg(feature = "sha")]
-use super::Sha256Hasher;
+use super::HasherRegistry;
+use super::MIN_SALT_SIZE;
+use crate::encoder::remove_property;
+use crate::hasher::encoded_digests;
+use crate::pointer::join_pointer;
 use crate::Error;
 use serde_json::Map;
 use serde_json::Value;
-use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+/// A decoded object and the `(digest, path)` pairs of the disclosures resolved while decoding it.
+pub(crate) type DecodedWithPaths = (Map<String, Value>, Vec<(String, String)>);
 
 /// Substitutes digests in an SD-JWT object by their corresponding plain text values provided by disclosures.
+///
+/// ## Note
+///
+/// The decoder works on the claims of the issuer-signed JWT, however they were obtained. It does not verify
+/// the JWT's signature, this must be done by the caller before trusting the decoded object.
 pub struct SdObjectDecoder {
-  hashers: BTreeMap<String, Box<dyn Hasher>>,
+  hashers: HasherRegistry,
+  options: DecodingOptions,
 }
 
 impl SdObjectDecoder {
   /// Creates a new [`SdObjectDecoder`] with `sha-256` hasher.
   #[cfg(feature = "sha")]
   pub fn new_with_sha256() -> Self {
-    let hashers: BTreeMap<String, Box<dyn Hasher>> = BTreeMap::new();
-    let mut hasher = Self { hashers };
-    hasher.add_hasher(Box::new(Sha256Hasher::new()));
-    hasher
+    Self::with_registry(HasherRegistry::with_sha256())
   }
 
   /// Creates a new [`SdObjectDecoder`] without any hashers.
   pub fn new() -> Self {
-    let hashers: BTreeMap<String, Box<dyn Hasher>> = BTreeMap::new();
-    Self { hashers }
+    Self::with_registry(HasherRegistry::new())
+  }
+
+  /// Creates a new [`SdObjectDecoder`] using the hashers of `registry`.
+  pub fn with_registry(registry: HasherRegistry) -> Self {
+    Self {
+      hashers: registry,
+      options: DecodingOptions::default(),
+    }
+  }
+
+  /// Returns the hashers used for decoding.
+  pub fn registry(&self) -> &HasherRegistry {
+    &self.hashers
   }
 
   /// Adds a hasher.
   ///
   /// If a hasher for the same algorithm [`Hasher::alg_name`] already exists, it will be replaced and
   /// the existing hasher will be returned, otherwise `None`.
-  pub fn add_hasher(&mut self, hasher: Box<dyn Hasher>) -> Option<Box<dyn Hasher>> {
-    let alg_name = hasher.as_ref().alg_name().to_string();
-
-    self.hashers.insert(alg_name.clone(), hasher)
+  ///
+  /// ## Error
+  /// Returns [`Error::UnregisteredHashAlgorithm`] if the algorithm is not an IANA hash name, see
+  /// [`HasherRegistry::register`].
+  pub fn add_hasher(&mut self, hasher: Box<dyn Hasher>) -> Result<Option<Box<dyn Hasher>>, Error> {
+    self.hashers.register(hasher)
   }
 
   /// Removes a hasher.
@@ -53,14 +78,40 @@ impl SdObjectDecoder {
     self.hashers.remove(&hash_alg)
   }
 
+  /// Returns the options used for decoding, e.g. to resolve an [`SdJwt`](crate::SdJwt) the same way with a
+  /// [`DecodingContext`].
+  pub fn options(&self) -> &DecodingOptions {
+    &self.options
+  }
+
+  /// Sets how disclosures that do not match any digest are handled, [`UnusedDisclosurePolicy::Error`]
+  /// by default.
+  pub fn set_unused_disclosure_policy(&mut self, policy: UnusedDisclosurePolicy) {
+    self.options.unused_disclosure_policy = policy;
+  }
+
+  /// Sets the maximum number of digests without matching disclosure, i.e. decoys and undisclosed claims,
+  /// accepted in total while decoding. Decoding fails as soon as the limit is exceeded, protecting against objects
+  /// stuffed with decoys. There is no limit by default.
+  pub fn set_max_unmatched_digests(&mut self, max: Option<usize>) {
+    self.options.max_unmatched_digests = max;
+  }
+
+  /// Sets the minimum size of the decoded salts of disclosures in bytes, [`MIN_SALT_SIZE`] by default.
+  /// Lowering it, e.g. to `0`, allows decoding legacy tokens with weak salts.
+  pub fn set_min_salt_size(&mut self, min_salt_size: usize) {
+    self.options.min_salt_size = min_salt_size;
+  }
+
   /// Decodes an SD-JWT `object` containing by Substituting the digests with their corresponding
   /// plain text values provided by `disclosures`.
   ///
   /// ## Notes
   /// * The hasher is determined by the `_sd_alg` property. If none is set, the sha-256 hasher will
-  /// be used, if present.
+  ///   be used, if present.
   /// * Claims like `exp` or `iat` are not validated in the process of decoding.
   /// * `_sd_alg` property will be removed if present.
+  #[allow(clippy::ptr_arg)]
   pub fn decode(
     &self,
     object: &Map<String, Value>,
@@ -68,109 +119,174 @@ impl SdObjectDecoder {
   ) -> Result<Map<String, Value>, crate::Error> {
     // Determine hasher.
     let hasher = self.determine_hasher(object)?;
+    let disclosures = parse_disclosures(hasher, disclosures, self.options.min_salt_size)?;
+    let mut state = self.decoding_state(&disclosures);
+    Self::decode_root(object, &mut state)
+  }
 
-    // Create a map of (disclosure digest) → (disclosure).
-    let mut disclosures_map: BTreeMap<String, Disclosure> = BTreeMap::new();
-    for disclosure in disclosures {
-      let parsed_disclosure = Disclosure::parse(disclosure.to_string())?;
-      let digest = hasher.encoded_digest(disclosure.as_str());
-      disclosures_map.insert(digest, parsed_disclosure);
-    }
+  /// Decodes `object` like [`SdObjectDecoder::decode`] using the hasher and options of `context` regardless of
+  /// `_sd_alg`. Additionally returns a `(digest, path)` pair for every resolved disclosure, where `path` is the
+  /// JSON pointer to the disclosed value in the decoded object. The pairs are in the order the digests were
+  /// resolved.
+  pub(crate) fn decode_with_paths(
+    context: DecodingContext<'_>,
+    object: &Map<String, Value>,
+    disclosures: &[String],
+  ) -> Result<DecodedWithPaths, Error> {
+    let disclosures = parse_disclosures(context.hasher, disclosures, context.options.min_salt_size)?;
+    let mut state = DecodingState::new(&disclosures);
+    state.unused_disclosure_policy = context.options.unused_disclosure_policy;
+    state.max_unmatched_digests = context.options.max_unmatched_digests;
+    let decoded = Self::decode_root(object, &mut state)?;
+    Ok((decoded, state.processed_digests))
+  }
+
+  /// Decodes `object` like [`SdObjectDecoder::decode`] and reports which disclosure revealed which claim
+  /// and which digests could not be resolved.
+  ///
+  /// ## Note
+  /// Unmatched digests are either decoys or belong to claims the holder did not disclose, the two cases
+  /// are indistinguishable by design.
+  ///
+  /// ## Error
+  /// Same as [`SdObjectDecoder::decode`].
+  pub fn decode_with_report(&self, object: &Map<String, Value>, disclosures: &[String]) -> Result<DecodeReport, Error> {
+    let hasher = self.determine_hasher(object)?;
+    let disclosures = parse_disclosures(hasher, disclosures, self.options.min_salt_size)?;
+    let mut state = self.decoding_state(&disclosures);
+    let object = Self::decode_root(object, &mut state)?;
+
+    let unused_disclosures = match state.unused_disclosure_policy {
+      UnusedDisclosurePolicy::Warn | UnusedDisclosurePolicy::ReturnUnused => disclosures
+        .iter()
+        .filter(|(digest, _)| !state.is_processed(digest))
+        .map(|(_, disclosure)| disclosure.clone())
+        .collect(),
+      _ => vec![],
+    };
+
+    let disclosed = std::mem::take(&mut state.processed_digests)
+      .into_iter()
+      .filter_map(|(digest, path)| {
+        state.find_disclosure(&digest).map(|disclosure| DisclosedClaim {
+          path,
+          digest,
+          disclosure: disclosure.clone(),
+        })
+      })
+      .collect();
+    let unmatched_digests = state
+      .unmatched_digests
+      .into_iter()
+      .map(|(digest, path)| UnmatchedDigest { path, digest })
+      .collect();
+
+    Ok(DecodeReport {
+      object,
+      disclosed,
+      unmatched_digests,
+      unused_disclosures,
+    })
+  }
 
-    // `processed_digests` are kept track of in case one digest appears more than once which
-    // renders the SD-JWT invalid.
-    let mut processed_digests: Vec<String> = vec![];
+  fn decoding_state<'a>(&self, disclosures: &'a [(String, Disclosure)]) -> DecodingState<'a> {
+    let mut state = DecodingState::new(disclosures);
+    state.unused_disclosure_policy = self.options.unused_disclosure_policy;
+    state.max_unmatched_digests = self.options.max_unmatched_digests;
+    state
+  }
 
+  fn decode_root(object: &Map<String, Value>, state: &mut DecodingState<'_>) -> Result<Map<String, Value>, Error> {
     // Decode the object recursively.
-    let mut decoded = self.decode_object(object, &disclosures_map, &mut processed_digests)?;
+    let mut decoded = Self::decode_object(object, "", state)?;
 
-    if processed_digests.len() != disclosures.len() {
-      return Err(crate::Error::UnusedDisclosures(
-        disclosures.len().saturating_sub(processed_digests.len()),
+    if state.unused_disclosure_policy == UnusedDisclosurePolicy::Error
+      && state.processed_digests.len() != state.disclosure_count
+    {
+      return Err(Error::UnusedDisclosures(
+        state.disclosure_count.saturating_sub(state.processed_digests.len()),
       ));
     }
 
     // Remove `_sd_alg` in case it exists.
-    decoded.remove(SD_ALG);
+    remove_property(&mut decoded, SD_ALG);
     Ok(decoded)
   }
 
   pub fn determine_hasher(&self, object: &Map<String, Value>) -> Result<&dyn Hasher, Error> {
-    //If the _sd_alg claim is not present at the top level, a default value of sha-256 MUST be used.
-    let alg: &str = if let Some(alg) = object.get(SD_ALG) {
-      alg.as_str().ok_or(Error::DataTypeMismatch(
-        "the value of `_sd_alg` is not a string".to_string(),
-      ))?
-    } else {
-      SHA_ALG_NAME
-    };
-    self
-      .hashers
-      .get(alg)
-      .map(AsRef::as_ref)
-      .ok_or(Error::MissingHasher(alg.to_string()))
+    self.hashers.determine_hasher(object)
   }
 
   fn decode_object(
-    &self,
     object: &Map<String, Value>,
-    disclosures: &BTreeMap<String, Disclosure>,
-    processed_digests: &mut Vec<String>,
+    path: &str,
+    state: &mut DecodingState<'_>,
   ) -> Result<Map<String, Value>, Error> {
     let mut output: Map<String, Value> = object.clone();
     for (key, value) in object.iter() {
       if key == DIGESTS_KEY {
-        let sd_array: &Vec<Value> = value
-          .as_array()
-          .ok_or(Error::DataTypeMismatch(format!("{} is not an array", DIGESTS_KEY)))?;
+        let sd_array: &Vec<Value> = value.as_array().ok_or_else(|| Error::DataTypeMismatch {
+          path: join_pointer(path, DIGESTS_KEY),
+          reason: format!("{} is not an array", DIGESTS_KEY),
+        })?;
         for digest in sd_array {
           let digest_str = digest
             .as_str()
-            .ok_or(Error::DataTypeMismatch(format!("{} is not a string", digest)))?
+            .ok_or_else(|| Error::DataTypeMismatch {
+              path: join_pointer(path, DIGESTS_KEY),
+              reason: format!("{} is not a string", digest),
+            })?
             .to_string();
 
           // Reject if any digests were found more than once.
-          if processed_digests.contains(&digest_str) {
-            return Err(Error::DuplicateDigestError(digest_str));
+          if state.is_processed(&digest_str) {
+            return Err(Error::DuplicateDigestError {
+              path: path.to_owned(),
+              digest: digest_str,
+            });
           }
 
           // Check if a disclosure of this digest is available
           // and insert its claim name and value in the object.
-          if let Some(disclosure) = disclosures.get(&digest_str) {
-            let claim_name = disclosure.claim_name.clone().ok_or(Error::DataTypeMismatch(format!(
-              "disclosure type error: {}",
-              disclosure
-            )))?;
+          if let Some(disclosure) = state.find_disclosure(&digest_str) {
+            let claim_name = disclosure.claim_name.clone().ok_or_else(|| Error::DataTypeMismatch {
+              path: path.to_owned(),
+              reason: format!("array element disclosure with digest {} used in an object", digest_str),
+            })?;
 
             if output.contains_key(&claim_name) {
-              return Err(Error::ClaimCollisionError(claim_name));
+              return Err(Error::ClaimCollisionError {
+                path: path.to_owned(),
+                claim_name,
+              });
             }
-            processed_digests.push(digest_str.clone());
+            let claim_path = join_pointer(path, &claim_name);
+            state.push_processed_digest(digest_str, claim_path.clone());
 
             let recursively_decoded = match disclosure.claim_value {
-              Value::Array(ref sub_arr) => Value::Array(self.decode_array(sub_arr, disclosures, processed_digests)?),
-              Value::Object(ref sub_obj) => {
-                Value::Object(self.decode_object(sub_obj, disclosures, processed_digests)?)
-              }
+              Value::Array(ref sub_arr) => Value::Array(Self::decode_array(sub_arr, &claim_path, state)?),
+              Value::Object(ref sub_obj) => Value::Object(Self::decode_object(sub_obj, &claim_path, state)?),
               _ => disclosure.claim_value.clone(),
             };
 
             output.insert(claim_name, recursively_decoded);
+          } else {
+            state.push_unmatched_digest(digest_str, path)?;
           }
         }
-        output.remove(DIGESTS_KEY);
+        remove_property(&mut output, DIGESTS_KEY);
         continue;
       }
 
       match value {
         Value::Object(object) => {
-          let decoded_object = self.decode_object(object, disclosures, processed_digests)?;
+          let decoded_object = Self::decode_object(object, &join_pointer(path, key), state)?;
           if !decoded_object.is_empty() {
             output.insert(key.to_string(), Value::Object(decoded_object));
           }
         }
         Value::Array(array) => {
-          let decoded_array = self.decode_array(array, disclosures, processed_digests)?;
+          let decoded_array = Self::decode_array(array, &join_pointer(path, key), state)?;
           if !decoded_array.is_empty() {
             output.insert(key.to_string(), Value::Array(decoded_array));
           }
@@ -182,57 +298,58 @@ impl SdObjectDecoder {
     Ok(output)
   }
 
-  fn decode_array(
-    &self,
-    array: &[Value],
-    disclosures: &BTreeMap<String, Disclosure>,
-    processed_digests: &mut Vec<String>,
-  ) -> Result<Vec<Value>, Error> {
+  fn decode_array(array: &[Value], path: &str, state: &mut DecodingState<'_>) -> Result<Vec<Value>, Error> {
     let mut output: Vec<Value> = vec![];
     for value in array.iter() {
+      // Path of the element in the decoded array.
+      let element_path = join_pointer(path, &output.len().to_string());
       if let Some(object) = value.as_object() {
         for (key, value) in object.iter() {
           if key == ARRAY_DIGEST_KEY {
             if object.keys().len() != 1 {
-              return Err(Error::InvalidArrayDisclosureObject);
+              return Err(Error::InvalidArrayDisclosureObject { path: element_path });
             }
 
             let digest_in_array = value
               .as_str()
-              .ok_or(Error::DataTypeMismatch(format!("{} is not a string", key)))?
+              .ok_or_else(|| Error::DataTypeMismatch {
+                path: join_pointer(&element_path, key),
+                reason: format!("{} is not a string", key),
+              })?
               .to_string();
 
             // Reject if any digests were found more than once.
-            if processed_digests.contains(&digest_in_array) {
-              return Err(Error::DuplicateDigestError(digest_in_array));
+            if state.is_processed(&digest_in_array) {
+              return Err(Error::DuplicateDigestError {
+                path: path.to_owned(),
+                digest: digest_in_array,
+              });
             }
-            if let Some(disclosure) = disclosures.get(&digest_in_array) {
+            if let Some(disclosure) = state.find_disclosure(&digest_in_array) {
               if disclosure.claim_name.is_some() {
                 return Err(Error::InvalidDisclosure("array length must be 2".to_string()));
               }
-              processed_digests.push(digest_in_array.clone());
+              state.push_processed_digest(digest_in_array, element_path.clone());
               // Recursively decoded the disclosed values.
               let recursively_decoded = match disclosure.claim_value {
-                Value::Array(ref sub_arr) => {
-                  Value::Array(self.decode_array(sub_arr, disclosures, processed_digests)?)
-                }
-                Value::Object(ref sub_obj) => {
-                  Value::Object(self.decode_object(sub_obj, disclosures, processed_digests)?)
-                }
+                Value::Array(ref sub_arr) => Value::Array(Self::decode_array(sub_arr, &element_path, state)?),
+                Value::Object(ref sub_obj) => Value::Object(Self::decode_object(sub_obj, &element_path, state)?),
                 _ => disclosure.claim_value.clone(),
               };
 
               output.push(recursively_decoded);
+            } else {
+              state.push_unmatched_digest(digest_in_array, path)?;
             }
           } else {
-            let decoded_object = self.decode_object(object, disclosures, processed_digests)?;
+            let decoded_object = Self::decode_object(object, &element_path, state)?;
             output.push(Value::Object(decoded_object));
             break;
           }
         }
       } else if let Some(arr) = value.as_array() {
         // Nested arrays need to be decoded too.
-        let decoded = self.decode_array(arr, disclosures, processed_digests)?;
+        let decoded = Self::decode_array(arr, &element_path, state)?;
         output.push(Value::Array(decoded));
       } else {
         // Append the rest of the values.
@@ -244,10 +361,227 @@ impl SdObjectDecoder {
   }
 }
 
+/// Result of [`SdObjectDecoder::decode_with_report`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodeReport {
+  /// The decoded object.
+  pub object: Map<String, Value>,
+  /// The claims revealed by disclosures, in the order their digests were resolved.
+  pub disclosed: Vec<DisclosedClaim>,
+  /// The digests no disclosure was provided for.
+  pub unmatched_digests: Vec<UnmatchedDigest>,
+  /// The disclosures that do not match any digest, always empty unless the decoder's
+  /// [`UnusedDisclosurePolicy`] is [`UnusedDisclosurePolicy::Warn`] or [`UnusedDisclosurePolicy::ReturnUnused`].
+  pub unused_disclosures: Vec<Disclosure>,
+}
+
+/// How [`SdObjectDecoder`] and the [`SdJwt`](crate::SdJwt) APIs handle disclosures that do not match any
+/// digest, e.g. disclosures a holder keeps locally.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum UnusedDisclosurePolicy {
+  /// Decoding fails with [`Error::UnusedDisclosures`].
+  #[default]
+  Error,
+  /// Unused disclosures are skipped and reported as warnings by [`SdJwt::validate_full`](crate::SdJwt::validate_full).
+  /// Like [`UnusedDisclosurePolicy::ReturnUnused`], they are returned by [`SdObjectDecoder::decode_with_report`] and
+  /// [`SdJwt::unused_disclosures`](crate::SdJwt::unused_disclosures).
+  Warn,
+  /// Unused disclosures are skipped and returned in [`DecodeReport::unused_disclosures`] and by
+  /// [`SdJwt::unused_disclosures`](crate::SdJwt::unused_disclosures).
+  ReturnUnused,
+  /// Unused disclosures are skipped silently.
+  Ignore,
+}
+
+/// Options of decoding, see [`SdObjectDecoder::options`] and [`DecodingContext`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecodingOptions {
+  /// How disclosures that do not match any digest are handled.
+  pub unused_disclosure_policy: UnusedDisclosurePolicy,
+  /// Maximum number of digests without matching disclosure, see [`SdObjectDecoder::set_max_unmatched_digests`].
+  /// There is no limit if `None`.
+  pub max_unmatched_digests: Option<usize>,
+  /// Minimum size of the decoded salts of disclosures in bytes, see [`SdObjectDecoder::set_min_salt_size`].
+  pub min_salt_size: usize,
+}
+
+impl Default for DecodingOptions {
+  fn default() -> Self {
+    Self {
+      unused_disclosure_policy: UnusedDisclosurePolicy::default(),
+      max_unmatched_digests: None,
+      min_salt_size: MIN_SALT_SIZE,
+    }
+  }
+}
+
+/// The hasher and options used by the [`SdJwt`](crate::SdJwt) APIs to resolve disclosures.
+///
+/// Every [`Hasher`] converts into a context with the default [`DecodingOptions`], so passing `&hasher` is
+/// enough unless the options need to be changed, e.g. to accept disclosures not matching any digest.
+#[derive(Clone, Copy)]
+pub struct DecodingContext<'a> {
+  /// The hasher of the algorithm specified by `_sd_alg`.
+  pub hasher: &'a dyn Hasher,
+  /// The options of decoding.
+  pub options: DecodingOptions,
+}
+
+impl<'a> DecodingContext<'a> {
+  /// Creates a new [`DecodingContext`].
+  pub fn new(hasher: &'a dyn Hasher, options: DecodingOptions) -> Self {
+    Self { hasher, options }
+  }
+}
+
+impl<'a, H: Hasher> From<&'a H> for DecodingContext<'a> {
+  fn from(hasher: &'a H) -> Self {
+    Self::new(hasher, DecodingOptions::default())
+  }
+}
+
+impl<'a> From<&'a dyn Hasher> for DecodingContext<'a> {
+  fn from(hasher: &'a dyn Hasher) -> Self {
+    Self::new(hasher, DecodingOptions::default())
+  }
+}
+
+impl std::fmt::Debug for DecodingContext<'_> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("DecodingContext")
+      .field("hasher", &self.hasher.alg_name())
+      .field("options", &self.options)
+      .finish()
+  }
+}
+
+/// A value revealed by a disclosure while decoding.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DisclosedClaim {
+  /// JSON pointer to the revealed value in the decoded object.
+  pub path: String,
+  /// The digest the disclosure matched.
+  pub digest: String,
+  /// The disclosure that revealed the value.
+  pub disclosure: Disclosure,
+}
+
+/// A digest without a matching disclosure.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnmatchedDigest {
+  /// JSON pointer to the object or array containing the digest, in the decoded object.
+  pub path: String,
+  /// The digest.
+  pub digest: String,
+}
+
+/// Bookkeeping of a single decoding.
+struct DecodingState<'a> {
+  /// The disclosures by their digest.
+  disclosures: HashMap<&'a str, &'a Disclosure>,
+  /// Number of disclosures, including duplicates.
+  disclosure_count: usize,
+  /// `(digest, path)` pairs of the resolved disclosures, in the order they were resolved.
+  processed_digests: Vec<(String, String)>,
+  /// The digests of `processed_digests`. They are kept track of in case one digest appears more than once
+  /// which renders the SD-JWT invalid.
+  processed: HashSet<String>,
+  /// `(digest, path)` pairs of the digests without a matching disclosure.
+  unmatched_digests: Vec<(String, String)>,
+  unused_disclosure_policy: UnusedDisclosurePolicy,
+  max_unmatched_digests: Option<usize>,
+}
+
+impl<'a> DecodingState<'a> {
+  fn new(disclosures: &'a [(String, Disclosure)]) -> Self {
+    Self {
+      disclosures: disclosures
+        .iter()
+        .map(|(digest, disclosure)| (digest.as_str(), disclosure))
+        .collect(),
+      disclosure_count: disclosures.len(),
+      processed_digests: vec![],
+      processed: HashSet::new(),
+      unmatched_digests: vec![],
+      unused_disclosure_policy: UnusedDisclosurePolicy::default(),
+      max_unmatched_digests: None,
+    }
+  }
+
+  /// Looks up the disclosure matching `digest`.
+  ///
+  /// The candidate found by the lookup is compared again with [`digests_equal`].
+  fn find_disclosure(&self, digest: &str) -> Option<&'a Disclosure> {
+    self
+      .disclosures
+      .get_key_value(digest)
+      .filter(|(candidate, _)| digests_equal(candidate, digest))
+      .map(|(_, disclosure)| *disclosure)
+  }
+
+  /// Returns whether the disclosure of `digest` was already resolved.
+  fn is_processed(&self, digest: &str) -> bool {
+    self.processed.contains(digest)
+  }
+
+  fn push_processed_digest(&mut self, digest: String, path: String) {
+    self.processed.insert(digest.clone());
+    self.processed_digests.push((digest, path));
+  }
+
+  fn push_unmatched_digest(&mut self, digest: String, path: &str) -> Result<(), Error> {
+    if let Some(max) = self.max_unmatched_digests {
+      if self.unmatched_digests.len() >= max {
+        return Err(Error::UnmatchedDigestLimitExceeded {
+          path: path.to_owned(),
+          max,
+        });
+      }
+    }
+    self.unmatched_digests.push((digest, path.to_owned()));
+    Ok(())
+  }
+}
+
+/// Parses `disclosures` with salts of at least `min_salt_size` bytes into a list of (disclosure digest,
+/// disclosure) pairs.
+fn parse_disclosures(
+  hasher: &dyn Hasher,
+  disclosures: &[String],
+  min_salt_size: usize,
+) -> Result<Vec<(String, Disclosure)>, Error> {
+  encoded_digests(hasher, disclosures)
+    .into_iter()
+    .zip(disclosures)
+    .map(|(digest, disclosure)| {
+      Ok((
+        digest,
+        Disclosure::parse_with_min_salt_size(disclosure.to_string(), min_salt_size)?,
+      ))
+    })
+    .collect()
+}
+
+/// Returns the name of the hash algorithm used in `object`.
+pub(crate) fn sd_alg(object: &Map<String, Value>) -> Result<&str, Error> {
+  //If the _sd_alg claim is not present at the top level, a default value of sha-256 MUST be used.
+  if let Some(alg) = object.get(SD_ALG) {
+    alg.as_str().ok_or_else(|| Error::DataTypeMismatch {
+      path: join_pointer("", SD_ALG),
+      reason: "the value of `_sd_alg` is not a string".to_string(),
+    })
+  } else {
+    Ok(SHA_ALG_NAME)
+  }
+}
+
+/// Creates a decoder with all hashers provided by the enabled features, see
+/// [`HasherRegistry::with_available_hashers`].
 #[cfg(feature = "sha")]
 impl Default for SdObjectDecoder {
   fn default() -> Self {
-    Self::new_with_sha256()
+    Self::with_registry(HasherRegistry::with_available_hashers())
   }
 }
 
@@ -255,8 +589,12 @@ impl Default for SdObjectDecoder {
 mod test {
   use crate::Disclosure;
   use crate::Error;
+  use crate::ErrorKind;
+  use crate::Hasher;
   use crate::SdObjectDecoder;
   use crate::SdObjectEncoder;
+  use crate::Sha256Hasher;
+  use crate::UnusedDisclosurePolicy;
   use serde_json::json;
   use serde_json::Value;
 
@@ -276,7 +614,142 @@ mod test {
     let decoded = decoder
       .decode(encoder.object().unwrap(), &vec![dis.to_string()])
       .unwrap_err();
-    assert!(matches!(decoded, Error::ClaimCollisionError(_)));
+    assert!(matches!(decoded, Error::ClaimCollisionError { .. }));
+    assert_eq!(decoded.path(), Some(""));
+  }
+
+  #[test]
+  fn nested_collision_path() {
+    let object = json!({
+      "address": {
+        "country": "DE",
+      }
+    });
+    let mut encoder = SdObjectEncoder::try_from(object).unwrap();
+    let disclosure = encoder.conceal("/address/country", None).unwrap();
+    encoder.object.as_object_mut().unwrap()["address"]
+      .as_object_mut()
+      .unwrap()
+      .insert("country".to_string(), Value::String("US".to_string()));
+    let decoder = SdObjectDecoder::new_with_sha256();
+    let error = decoder
+      .decode(encoder.object().unwrap(), &vec![disclosure.to_string()])
+      .unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::ClaimCollision);
+    assert_eq!(error.path(), Some("/address"));
+  }
+
+  #[test]
+  fn decode_with_report() {
+    let object = json!({
+      "id": "did:value",
+      "nationalities": ["DE", "US"]
+    });
+    let mut encoder = SdObjectEncoder::try_from(object).unwrap();
+    let id = encoder.conceal("/id", None).unwrap();
+    encoder.conceal("/nationalities/0", None).unwrap();
+    let nationality = encoder.conceal("/nationalities/1", None).unwrap();
+    encoder.add_decoys("", 2).unwrap();
+    let decoder = SdObjectDecoder::new_with_sha256();
+    let report = decoder
+      .decode_with_report(encoder.object().unwrap(), &[id.to_string(), nationality.to_string()])
+      .unwrap();
+    assert_eq!(
+      Value::Object(report.object),
+      json!({ "id": "did:value", "nationalities": ["US"] })
+    );
+    let disclosed: Vec<(&str, &Disclosure)> = report
+      .disclosed
+      .iter()
+      .map(|claim| (claim.path.as_str(), &claim.disclosure))
+      .collect();
+    assert!(disclosed.contains(&("/id", &id)));
+    assert!(disclosed.contains(&("/nationalities/0", &nationality)));
+    assert_eq!(report.unmatched_digests.len(), 3);
+    assert_eq!(
+      report
+        .unmatched_digests
+        .iter()
+        .filter(|unmatched| unmatched.path == "/nationalities")
+        .count(),
+      1
+    );
+  }
+
+  #[test]
+  fn max_unmatched_digests() {
+    let mut encoder = SdObjectEncoder::try_from(json!({ "id": "did:value" })).unwrap();
+    let id = encoder.conceal("/id", None).unwrap();
+    encoder.add_decoys("", 3).unwrap();
+    let mut decoder = SdObjectDecoder::new_with_sha256();
+    decoder.set_max_unmatched_digests(Some(3));
+    decoder
+      .decode(encoder.object().unwrap(), &vec![id.to_string()])
+      .unwrap();
+    let error = decoder.decode(encoder.object().unwrap(), &vec![]).unwrap_err();
+    assert!(matches!(error, Error::UnmatchedDigestLimitExceeded { max: 3, .. }));
+    assert_eq!(error.path(), Some(""));
+  }
+
+  #[cfg(feature = "preserve_order")]
+  #[test]
+  fn preserve_order() {
+    let object = json!({
+      "iss": "https://issuer.example.com",
+      "given_name": "John",
+      "family_name": "Doe",
+      "address": {
+        "street_address": "Schulstr. 12",
+        "locality": "Schulpforta"
+      },
+      "birthdate": "1940-01-01"
+    });
+    let mut encoder = SdObjectEncoder::try_from(object.clone()).unwrap();
+    encoder.add_sd_alg_property();
+    let family_name = encoder.conceal("/family_name", None).unwrap();
+    let decoder = SdObjectDecoder::new_with_sha256();
+    let decoded = decoder
+      .decode(encoder.object().unwrap(), &vec![family_name.to_string()])
+      .unwrap();
+    // Plain text claims keep their order, disclosed claims are appended.
+    let keys: Vec<&str> = decoded.keys().map(String::as_str).collect();
+    assert_eq!(keys, vec!["iss", "given_name", "address", "birthdate", "family_name"]);
+    let address: Vec<&String> = decoded["address"].as_object().unwrap().keys().collect();
+    assert_eq!(address, vec!["street_address", "locality"]);
+  }
+
+  #[cfg(feature = "arbitrary_precision")]
+  #[test]
+  fn arbitrary_precision() {
+    let input = r#"{"id":123456789012345678901234567890,"amount":0.10,"limits":[18446744073709551617]}"#;
+    let mut encoder = SdObjectEncoder::new(input).unwrap();
+    let id = encoder.conceal("/id", None).unwrap();
+    let amount = encoder.conceal("/amount", None).unwrap();
+    let limit = encoder.conceal("/limits/0", None).unwrap();
+    assert_eq!(
+      Disclosure::parse(id.to_string()).unwrap().claim_value.to_string(),
+      "123456789012345678901234567890"
+    );
+
+    let decoder = SdObjectDecoder::new_with_sha256();
+    let disclosures = vec![id.to_string(), amount.to_string(), limit.to_string()];
+    let decoded = decoder.decode(encoder.object().unwrap(), &disclosures).unwrap();
+    let expected: Value = serde_json::from_str(input).unwrap();
+    assert_eq!(Value::Object(decoded), expected);
+    assert_eq!(expected["amount"].to_string(), "0.10");
+  }
+
+  #[cfg(feature = "sha-512")]
+  #[test]
+  fn default_hashers() {
+    let mut encoder =
+      SdObjectEncoder::from_value_with_hasher(json!({ "id": "did:value" }), crate::Sha512Hasher::new()).unwrap();
+    let disclosure = encoder.conceal("/id", None).unwrap();
+    encoder.add_sd_alg_property();
+    let decoded = SdObjectDecoder::default()
+      .decode(encoder.object().unwrap(), &vec![disclosure.into_string()])
+      .unwrap();
+    assert_eq!(decoded.get("id").unwrap(), "did:value");
   }
 
   #[test]
@@ -301,18 +774,24 @@ mod test {
       "id": "did:value",
     });
     let mut encoder = SdObjectEncoder::try_from(object).unwrap();
-    let dislosure: Disclosure = encoder.conceal("/id", Some("test".to_string())).unwrap();
+    let dislosure: Disclosure = encoder
+      .conceal("/id", Some("2GLC42sKQveCfGfryNRN9w".to_string()))
+      .unwrap();
     // 'obj' contains digest of `id` twice.
+    let digest = Sha256Hasher::new().encoded_digest(dislosure.as_str());
     let obj = json!({
       "_sd":[
-        "mcKLMnXQdCM0gJ5l4Hb6ignpVgCw4SfienkI8vFgpjE",
-        "mcKLMnXQdCM0gJ5l4Hb6ignpVgCw4SfienkI8vFgpjE"
+        digest,
+        digest
       ]
       }
     );
     let decoder = SdObjectDecoder::new_with_sha256();
     let result = decoder.decode(obj.as_object().unwrap(), &vec![dislosure.to_string()]);
-    assert!(matches!(result.err().unwrap(), crate::Error::DuplicateDigestError(_)));
+    assert!(matches!(
+      result.err().unwrap(),
+      crate::Error::DuplicateDigestError { .. }
+    ));
   }
 
   #[test]
@@ -322,12 +801,16 @@ mod test {
       "tst": "tst-value"
     });
     let mut encoder = SdObjectEncoder::try_from(object).unwrap();
-    let disclosure_1: Disclosure = encoder.conceal("/id", Some("test".to_string())).unwrap();
-    let disclosure_2: Disclosure = encoder.conceal("/tst", Some("test".to_string())).unwrap();
+    let disclosure_1: Disclosure = encoder
+      .conceal("/id", Some("2GLC42sKQveCfGfryNRN9w".to_string()))
+      .unwrap();
+    let disclosure_2: Disclosure = encoder
+      .conceal("/tst", Some("2GLC42sKQveCfGfryNRN9w".to_string()))
+      .unwrap();
     // 'obj' contains only the digest of `id`.
     let obj = json!({
       "_sd":[
-        "mcKLMnXQdCM0gJ5l4Hb6ignpVgCw4SfienkI8vFgpjE",
+        Sha256Hasher::new().encoded_digest(disclosure_1.as_str()),
       ]
       }
     );
@@ -337,5 +820,21 @@ mod test {
       &vec![disclosure_1.to_string(), disclosure_2.to_string()],
     );
     assert!(matches!(result.err().unwrap(), crate::Error::UnusedDisclosures(1)));
+
+    let mut decoder = SdObjectDecoder::new_with_sha256();
+    decoder.set_unused_disclosure_policy(UnusedDisclosurePolicy::Ignore);
+    let disclosures = vec![disclosure_1.to_string(), disclosure_2.to_string()];
+    let decoded = decoder.decode(obj.as_object().unwrap(), &disclosures).unwrap();
+    assert_eq!(Value::Object(decoded), json!({ "id": "did:value" }));
+    let report = decoder
+      .decode_with_report(obj.as_object().unwrap(), &disclosures)
+      .unwrap();
+    assert!(report.unused_disclosures.is_empty());
+
+    decoder.set_unused_disclosure_policy(UnusedDisclosurePolicy::ReturnUnused);
+    let report = decoder
+      .decode_with_report(obj.as_object().unwrap(), &disclosures)
+      .unwrap();
+    assert_eq!(report.unused_disclosures, vec![disclosure_2]);
   }
 }
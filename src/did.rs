@@ -0,0 +1,284 @@
+// Copyright 2020-2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Conversion of holder keys from and to [`did:jwk`](https://github.com/quartzjer/did-jwk/blob/main/spec.md)
+//! and [`did:key`](https://w3c-ccg.github.io/did-method-key/) identifiers, which wallets use to express the
+//! holder's key in `cnf` instead of an embedded JWK.
+
+use serde_json::Map;
+use serde_json::Value;
+
+use crate::Error;
+use crate::Jwk;
+use crate::RequiredKeyBinding;
+use crate::Result;
+
+const DID_JWK_PREFIX: &str = "did:jwk:";
+const DID_KEY_PREFIX: &str = "did:key:";
+
+/// Members of private and symmetric keys, which must not end up in a public identifier.
+const PRIVATE_MEMBERS: [&str; 8] = ["d", "p", "q", "dp", "dq", "qi", "oth", "k"];
+
+/// Multicodec prefixes, as unsigned varints, of the public key types supported by `did:key`.
+const MULTICODECS: [(&str, [u8; 2]); 2] = [("Ed25519", [0xed, 0x01]), ("X25519", [0xec, 0x01])];
+
+/// Size in bytes of the public keys of the curves in [`MULTICODECS`].
+const KEY_SIZE: usize = 32;
+
+impl Jwk {
+  /// Returns the `did:jwk` identifier of this key.
+  ///
+  /// ## Error
+  /// Returns [`Error::InvalidDid`] if this is a symmetric (`oct`) key or carries private key members.
+  pub fn to_did_jwk(&self) -> Result<String> {
+    if self.kty() == "oct" {
+      return Err(Error::InvalidDid("oct key can not be expressed as did:jwk".to_string()));
+    }
+    if let Some(member) = PRIVATE_MEMBERS
+      .iter()
+      .find(|member| self.params().contains_key(**member))
+    {
+      return Err(Error::InvalidDid(format!(
+        "private key member `{}` can not be expressed as did:jwk",
+        member
+      )));
+    }
+    Ok(format!(
+      "{}{}",
+      DID_JWK_PREFIX,
+      crate::base64::encode(crate::canonicalize(&Value::Object(self.params().clone())))
+    ))
+  }
+
+  /// Parses the key of the `did:jwk` identifier `did`, a DID URL with fragment is accepted.
+  ///
+  /// ## Error
+  /// Returns [`Error::InvalidDid`] if `did` is not a `did:jwk` identifier or does not encode a valid JWK.
+  pub fn from_did_jwk(did: &str) -> Result<Self> {
+    let encoded = strip_did(did, DID_JWK_PREFIX)?;
+    let decoded = crate::base64::decode(encoded).map_err(|_| Error::InvalidDid(did.to_owned()))?;
+    let params: Map<String, Value> = serde_json::from_slice(&decoded).map_err(|_| Error::InvalidDid(did.to_owned()))?;
+    Jwk::try_from(params).map_err(|err| Error::InvalidDid(format!("{}: {}", did, err)))
+  }
+
+  /// Returns the `did:key` identifier of this key.
+  ///
+  /// ## Error
+  /// Returns [`Error::InvalidDid`] if this is not an `OKP` key of curve `Ed25519` or `X25519` with a
+  /// 32-byte `x`.
+  ///
+  /// ## Note
+  /// `did:key` encodes elliptic curve keys in compressed form, which can not be decompressed without curve
+  /// arithmetic, use [`Jwk::to_did_jwk`] for `EC` keys instead.
+  pub fn to_did_key(&self) -> Result<String> {
+    let codec = match (self.kty(), self.crv()) {
+      ("OKP", Some(crv)) => MULTICODECS
+        .iter()
+        .find(|(name, _)| *name == crv)
+        .map(|(_, codec)| codec),
+      _ => None,
+    }
+    .ok_or_else(|| Error::InvalidDid(format!("{} key can not be expressed as did:key", self.kty())))?;
+    let key = self
+      .x()
+      .and_then(|x| crate::base64::decode(x).ok())
+      .ok_or_else(|| Error::InvalidDid("`x` is not base64url-encoded".to_string()))?;
+    if key.len() != KEY_SIZE {
+      return Err(Error::InvalidDid(format!(
+        "`x` has {} bytes instead of {}",
+        key.len(),
+        KEY_SIZE
+      )));
+    }
+    let bytes: Vec<u8> = codec.iter().chain(&key).copied().collect();
+    Ok(format!(
+      "{}{}",
+      DID_KEY_PREFIX,
+      multibase::encode(multibase::Base::Base58Btc, bytes)
+    ))
+  }
+
+  /// Parses the key of the `did:key` identifier `did`, a DID URL with fragment is accepted.
+  ///
+  /// ## Error
+  /// Returns [`Error::InvalidDid`] if `did` is not a `did:key` identifier of a 32-byte `Ed25519` or `X25519`
+  /// key.
+  pub fn from_did_key(did: &str) -> Result<Self> {
+    let encoded = strip_did(did, DID_KEY_PREFIX)?;
+    let (base, bytes) = multibase::decode(encoded).map_err(|_| Error::InvalidDid(did.to_owned()))?;
+    if base != multibase::Base::Base58Btc {
+      return Err(Error::InvalidDid(format!("{} is not base58btc-encoded", did)));
+    }
+    let (crv, key) = MULTICODECS
+      .iter()
+      .find_map(|(crv, codec)| bytes.strip_prefix(codec.as_slice()).map(|key| (*crv, key)))
+      .ok_or_else(|| Error::InvalidDid(format!("{} has an unsupported key type", did)))?;
+    if key.len() != KEY_SIZE {
+      return Err(Error::InvalidDid(format!(
+        "{} has a key of {} bytes instead of {}",
+        did,
+        key.len(),
+        KEY_SIZE
+      )));
+    }
+    let mut params = Map::new();
+    params.insert("kty".to_owned(), "OKP".into());
+    params.insert("crv".to_owned(), crv.into());
+    params.insert("x".to_owned(), crate::base64::encode(key).into());
+    Jwk::try_from(params).map_err(|err| Error::InvalidDid(format!("{}: {}", did, err)))
+  }
+
+  /// Parses the key of the `did:jwk` or `did:key` identifier `did`.
+  ///
+  /// ## Error
+  /// Same as [`Jwk::from_did_jwk`] and [`Jwk::from_did_key`], other DID methods are rejected with
+  /// [`Error::InvalidDid`].
+  pub fn from_did(did: &str) -> Result<Self> {
+    if did.starts_with(DID_JWK_PREFIX) {
+      Self::from_did_jwk(did)
+    } else {
+      Self::from_did_key(did)
+    }
+  }
+}
+
+impl RequiredKeyBinding {
+  /// Creates a [`RequiredKeyBinding::Kid`] referencing `jwk` by the DID URL of its `did:jwk` identifier.
+  ///
+  /// ## Error
+  /// Same as [`Jwk::to_did_jwk`].
+  pub fn did_jwk(jwk: &Jwk) -> Result<Self> {
+    Ok(Self::Kid {
+      kid: format!("{}#0", jwk.to_did_jwk()?),
+    })
+  }
+
+  /// Creates a [`RequiredKeyBinding::Kid`] referencing `jwk` by the DID URL of its `did:key` identifier.
+  ///
+  /// ## Error
+  /// Same as [`Jwk::to_did_key`].
+  pub fn did_key(jwk: &Jwk) -> Result<Self> {
+    let did = jwk.to_did_key()?;
+    let fragment = did[DID_KEY_PREFIX.len()..].to_owned();
+    Ok(Self::Kid {
+      kid: format!("{}#{}", did, fragment),
+    })
+  }
+
+  /// Resolves the holder's key of a [`RequiredKeyBinding::Kid`] that is a `did:jwk` or `did:key` identifier.
+  /// For [`RequiredKeyBinding::Jwk`] the embedded key is returned, `None` for all other key IDs and variants.
+  ///
+  /// ## Error
+  /// Same as [`Jwk::from_did`].
+  pub fn resolve_did(&self) -> Result<Option<Jwk>> {
+    match self {
+      Self::Jwk { jwk } => Ok(Some(jwk.clone())),
+      Self::Kid { kid } if kid.starts_with(DID_JWK_PREFIX) || kid.starts_with(DID_KEY_PREFIX) => {
+        Jwk::from_did(kid).map(Some)
+      }
+      _ => Ok(None),
+    }
+  }
+}
+
+/// Returns the method-specific identifier of `did` without its fragment.
+fn strip_did<'a>(did: &'a str, prefix: &str) -> Result<&'a str> {
+  let identifier = did
+    .strip_prefix(prefix)
+    .ok_or_else(|| Error::InvalidDid(format!("{} does not start with {}", did, prefix)))?;
+  Ok(
+    identifier
+      .split_once('#')
+      .map_or(identifier, |(identifier, _)| identifier),
+  )
+}
+
+#[cfg(test)]
+mod test {
+  use crate::Error;
+  use crate::Jwk;
+  use crate::RequiredKeyBinding;
+  use serde_json::json;
+
+  #[test]
+  fn did_jwk() {
+    let jwk: Jwk = serde_json::from_value(json!({
+      "kty": "EC",
+      "crv": "P-256",
+      "x": "acbIQiuMs3i8_uszEjJ2tpTtRM4EU3yz91PH6CdH2V0",
+      "y": "_KcyLj9vWMptnmKtm46GqDz8wf74I5LKgrl2GzH3nSE"
+    }))
+    .unwrap();
+    // Example of the did:jwk specification, whose JSON is serialized the same way.
+    let did = jwk.to_did_jwk().unwrap();
+    assert_eq!(
+      did,
+      "did:jwk:eyJjcnYiOiJQLTI1NiIsImt0eSI6IkVDIiwieCI6ImFjYklRaXVNczNpOF91c3pFakoydHBUdFJNNEVVM3l6OTFQSDZDZEgyVjAiLC\
+       J5IjoiX0tjeUxqOXZXTXB0bm1LdG00NkdxRHo4d2Y3NEk1TEtncmwyR3pIM25TRSJ9"
+    );
+    let binding = RequiredKeyBinding::did_jwk(&jwk).unwrap();
+    assert_eq!(binding.resolve_did().unwrap(), Some(jwk));
+    assert!(matches!(Jwk::from_did_jwk("did:jwk:e30"), Err(Error::InvalidDid(_))));
+  }
+
+  #[test]
+  fn did_jwk_rejects_private_keys() {
+    let private: Jwk = serde_json::from_value(json!({
+      "kty": "EC",
+      "crv": "P-256",
+      "x": "acbIQiuMs3i8_uszEjJ2tpTtRM4EU3yz91PH6CdH2V0",
+      "y": "_KcyLj9vWMptnmKtm46GqDz8wf74I5LKgrl2GzH3nSE",
+      "d": "42TK_ZDF5ADxNjPMOvtTTZ7hDq3ZKyd5E56RUoBHuIw"
+    }))
+    .unwrap();
+    assert!(matches!(private.to_did_jwk(), Err(Error::InvalidDid(_))));
+    assert!(matches!(
+      RequiredKeyBinding::did_jwk(&private),
+      Err(Error::InvalidDid(_))
+    ));
+
+    let symmetric: Jwk = serde_json::from_value(json!({
+      "kty": "oct",
+      "k": "GawgguFyGrWKav7AX4VKUg"
+    }))
+    .unwrap();
+    assert!(matches!(symmetric.to_did_jwk(), Err(Error::InvalidDid(_))));
+  }
+
+  #[test]
+  fn did_key() {
+    // Example of the did:key specification.
+    let did = "did:key:z6MkhaXgBZDvotDkL5257faiztiGiC2QtKLGpbnnEGta2doK";
+    let jwk = Jwk::from_did_key(did).unwrap();
+    assert_eq!(jwk.crv(), Some("Ed25519"));
+    assert_eq!(jwk.to_did_key().unwrap(), did);
+
+    let binding = RequiredKeyBinding::did_key(&jwk).unwrap();
+    assert_eq!(
+      binding,
+      RequiredKeyBinding::Kid {
+        kid: format!("{}#z6MkhaXgBZDvotDkL5257faiztiGiC2QtKLGpbnnEGta2doK", did)
+      }
+    );
+    assert_eq!(binding.resolve_did().unwrap(), Some(jwk));
+    assert_eq!(
+      RequiredKeyBinding::Kid {
+        kid: "key-1".to_owned()
+      }
+      .resolve_did()
+      .unwrap(),
+      None
+    );
+    assert!(Jwk::from_did("did:web:example.com").is_err());
+  }
+
+  #[test]
+  fn did_key_rejects_invalid_key_sizes() {
+    // Ed25519 keys of 3 and 0 bytes.
+    for did in ["did:key:zTjsU3jY", "did:key:zK36"] {
+      assert!(matches!(Jwk::from_did_key(did), Err(Error::InvalidDid(_))), "{}", did);
+    }
+    let jwk: Jwk = serde_json::from_value(json!({ "kty": "OKP", "crv": "Ed25519", "x": "AQID" })).unwrap();
+    assert!(matches!(jwk.to_did_key(), Err(Error::InvalidDid(_))));
+  }
+}
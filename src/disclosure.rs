@@ -7,11 +7,24 @@ use serde::Serialize;
 use serde_json::Value;
 use std::fmt::Display;
 
+/// Minimum size of the decoded salt of a [`Disclosure`] in bytes, i.e. the 128 bits recommended by the
+/// specification.
+pub const MIN_SALT_SIZE: usize = 16;
+
 /// Represents an elements constructing a disclosure.
 /// Object properties and array elements disclosures are supported.
 ///
 /// See: https://www.ietf.org/archive/id/draft-ietf-oauth-selective-disclosure-jwt-07.html#name-disclosures
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+///
+/// ## Note
+///
+/// Unless the `debug-plaintext` feature is enabled, the [`Debug`](std::fmt::Debug) output only contains the claim name
+/// while the salt, the claim value and the encoded disclosure are redacted.
+///
+/// With the `zeroize` feature enabled, [`Disclosure`] implements `Zeroize`, so all fields can be wiped from memory,
+/// e.g. when it is dropped by wrapping it in `Zeroizing<Disclosure>`.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "debug-plaintext", derive(Debug))]
 pub struct Disclosure {
   /// The salt value.
   pub salt: String,
@@ -27,30 +40,110 @@ impl Disclosure {
   /// Creates a new instance of [`Disclosure`].
   ///
   /// Use `.to_string()` to get the actual disclosure.
-  pub fn new(salt: String, claim_name: Option<String>, claim_value: Value) -> Self {
+  ///
+  /// ## Error
+  /// Same as [`Disclosure::validate_salt`] with [`MIN_SALT_SIZE`].
+  pub fn new(salt: String, claim_name: Option<String>, claim_value: Value) -> Result<Self, Error> {
+    Self::new_with_min_salt_size(salt, claim_name, claim_value, MIN_SALT_SIZE)
+  }
+
+  /// Same as [`Disclosure::new`], accepting salts of at least `min_salt_size` bytes, e.g. to recreate
+  /// disclosures of legacy tokens with weak salts.
+  ///
+  /// ## Error
+  /// Same as [`Disclosure::validate_salt`].
+  pub fn new_with_min_salt_size(
+    salt: String,
+    claim_name: Option<String>,
+    claim_value: Value,
+    min_salt_size: usize,
+  ) -> Result<Self, Error> {
+    Self::validate_salt(&salt, min_salt_size)?;
     let input = if let Some(name) = &claim_name {
       format!("[\"{}\", \"{}\", {}]", &salt, &name, &claim_value.to_string())
     } else {
       format!("[\"{}\", {}]", &salt, &claim_value.to_string())
     };
 
-    let encoded = multibase::Base::Base64Url.encode(input);
-    Self {
+    let encoded = crate::base64::encode(&input);
+    #[cfg(feature = "zeroize")]
+    drop(zeroize::Zeroizing::new(input));
+
+    Ok(Self {
       salt,
       claim_name,
       claim_value,
       disclosure: encoded,
+    })
+  }
+
+  /// Creates a new instance of [`Disclosure`] whose array is serialized using the
+  /// [JSON Canonicalization Scheme](https://www.rfc-editor.org/rfc/rfc8785), see [`canonicalize`](crate::canonicalize).
+  ///
+  /// Disclosures created from the same salt, claim name and claim value are identical, even if the value was
+  /// constructed differently, e.g. with another member order.
+  ///
+  /// ## Warning
+  /// JCS formats every number as an `f64`, so integers above 2^53, e.g. [`u64::MAX`], are rounded in the
+  /// encoded disclosure and differ from `claim_value` once parsed. Encode such values as strings.
+  ///
+  /// ## Error
+  /// Same as [`Disclosure::new`].
+  pub fn new_canonical(salt: String, claim_name: Option<String>, claim_value: Value) -> Result<Self, Error> {
+    Self::validate_salt(&salt, MIN_SALT_SIZE)?;
+    let array = match &claim_name {
+      Some(name) => serde_json::json!([salt, name, claim_value]),
+      None => serde_json::json!([salt, claim_value]),
+    };
+    let input = crate::canonicalize(&array);
+
+    let encoded = crate::base64::encode(&input);
+    #[cfg(feature = "zeroize")]
+    {
+      drop(zeroize::Zeroizing::new(input));
+      zeroize_value(array);
     }
+
+    Ok(Self {
+      salt,
+      claim_name,
+      claim_value,
+      disclosure: encoded,
+    })
+  }
+
+  /// Checks that `salt` is at least `min_size` bytes long, derived from its length as base64url-encoded
+  /// string.
+  ///
+  /// ## Error
+  /// Returns [`Error::SaltTooShort`] if the salt is shorter than `min_size` bytes.
+  pub fn validate_salt(salt: &str, min_size: usize) -> Result<(), Error> {
+    // Every base64url character encodes 6 bits.
+    let size = salt.trim_end_matches('=').len() * 3 / 4;
+    if size < min_size {
+      return Err(Error::SaltTooShort { size, min_size });
+    }
+    Ok(())
   }
 
   /// Parses a Base64 encoded disclosure into a [`Disclosure`].
   ///
   /// ## Error
   ///
-  /// Returns an [`Error::InvalidDisclosure`] if input is not a valid disclosure.
+  /// Returns an [`Error::InvalidDisclosure`] if input is not a valid disclosure and
+  /// [`Error::SaltTooShort`] if its salt is shorter than [`MIN_SALT_SIZE`], see
+  /// [`Disclosure::parse_with_min_salt_size`] for legacy tokens.
   pub fn parse(disclosure: String) -> Result<Self, Error> {
-    let decoded: Vec<Value> = multibase::Base::Base64Url
-      .decode(&disclosure)
+    Self::parse_with_min_salt_size(disclosure, MIN_SALT_SIZE)
+  }
+
+  /// Same as [`Disclosure::parse`], accepting salts of at least `min_salt_size` bytes, e.g. `0` to accept
+  /// disclosures of legacy tokens with weak salts.
+  ///
+  /// ## Error
+  /// Same as [`Disclosure::parse`] and [`Disclosure::validate_salt`].
+  pub fn parse_with_min_salt_size(disclosure: String, min_salt_size: usize) -> Result<Self, Error> {
+    let decoded: Vec<Value> = crate::base64::decode(&disclosure)
       .map_err(|_e| {
         Error::InvalidDisclosure(format!(
           "Base64 decoding of the disclosure was not possible {}",
@@ -58,6 +151,8 @@ impl Disclosure {
         ))
       })
       .and_then(|data| {
+        #[cfg(feature = "zeroize")]
+        let data = zeroize::Zeroizing::new(data);
         serde_json::from_slice(&data).map_err(|_e| {
           Error::InvalidDisclosure(format!(
             "decoded disclosure could not be serialized as an array {}",
@@ -65,57 +160,47 @@ impl Disclosure {
           ))
         })
       })?;
-
-    if decoded.len() == 2 {
-      Ok(Self {
-        salt: decoded
-          .first()
-          .ok_or(Error::InvalidDisclosure("invalid salt".to_string()))?
-          .as_str()
-          .ok_or(Error::InvalidDisclosure(
-            "salt could not be parsed as a string".to_string(),
-          ))?
-          .to_owned(),
-        claim_name: None,
-        claim_value: decoded
-          .get(1)
-          .ok_or(Error::InvalidDisclosure("invalid claim name".to_string()))?
-          .clone(),
-
-        disclosure,
-      })
-    } else if decoded.len() == 3 {
-      Ok(Self {
-        salt: decoded
-          .first()
-          .ok_or(Error::InvalidDisclosure("invalid salt".to_string()))?
-          .as_str()
-          .ok_or(Error::InvalidDisclosure(
-            "salt could not be parsed as a string".to_string(),
-          ))?
-          .to_owned(),
-        claim_name: Some(
-          decoded
-            .get(1)
-            .ok_or(Error::InvalidDisclosure("invalid claim name".to_string()))?
-            .as_str()
-            .ok_or(Error::InvalidDisclosure(
-              "claim name could not be parsed as a string".to_string(),
-            ))?
-            .to_owned(),
-        ),
-        claim_value: decoded
-          .get(2)
-          .ok_or(Error::InvalidDisclosure("invalid claim name".to_string()))?
-          .clone(),
-        disclosure,
-      })
-    } else {
-      Err(Error::InvalidDisclosure(format!(
+    if decoded.len() != 2 && decoded.len() != 3 {
+      return Err(Error::InvalidDisclosure(format!(
         "deserialized array has an invalid length of {}",
         decoded.len()
-      )))
+      )));
     }
+
+    // The elements are moved into the disclosure, so no copies of them are left behind.
+    let has_claim_name = decoded.len() == 3;
+    let mut elements = decoded.into_iter();
+    let salt = match elements.next() {
+      Some(Value::String(salt)) => salt,
+      _ => {
+        return Err(Error::InvalidDisclosure(
+          "salt could not be parsed as a string".to_string(),
+        ))
+      }
+    };
+    let claim_name = if has_claim_name {
+      match elements.next() {
+        Some(Value::String(claim_name)) => Some(claim_name),
+        _ => {
+          return Err(Error::InvalidDisclosure(
+            "claim name could not be parsed as a string".to_string(),
+          ))
+        }
+      }
+    } else {
+      None
+    };
+    let claim_value = elements
+      .next()
+      .ok_or(Error::InvalidDisclosure("invalid claim value".to_string()))?;
+    let parsed = Self {
+      salt,
+      claim_name,
+      claim_value,
+      disclosure,
+    };
+    Self::validate_salt(&parsed.salt, min_salt_size)?;
+    Ok(parsed)
   }
 
   /// Reference the actual disclosure.
@@ -129,15 +214,91 @@ impl Disclosure {
   }
 }
 
+#[cfg(feature = "zeroize")]
+impl zeroize::Zeroize for Disclosure {
+  fn zeroize(&mut self) {
+    self.salt.zeroize();
+    self.claim_name.zeroize();
+    zeroize_value(std::mem::take(&mut self.claim_value));
+    self.disclosure.zeroize();
+  }
+}
+
+/// Zeroizes all strings contained in `value`, including object keys.
+///
+/// Numbers are not zeroized. With the `arbitrary_precision` feature enabled, `serde_json` keeps them as strings
+/// that can not be accessed mutably.
+#[cfg(feature = "zeroize")]
+fn zeroize_value(value: Value) {
+  use zeroize::Zeroize;
+  match value {
+    Value::String(mut string) => string.zeroize(),
+    Value::Array(array) => array.into_iter().for_each(zeroize_value),
+    Value::Object(object) => {
+      for (mut key, value) in object {
+        key.zeroize();
+        zeroize_value(value);
+      }
+    }
+    Value::Null | Value::Bool(_) | Value::Number(_) => {}
+  }
+}
+
 impl Display for Disclosure {
   fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
     f.write_str(&self.disclosure)
   }
 }
 
+#[cfg(not(feature = "debug-plaintext"))]
+impl std::fmt::Debug for Disclosure {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("Disclosure")
+      .field("salt", &Redacted)
+      .field("claim_name", &self.claim_name)
+      .field("claim_value", &Redacted)
+      .field("disclosure", &Redacted)
+      .finish()
+  }
+}
+
+/// Placeholder used in [`Debug`](std::fmt::Debug) implementations in place of values that may contain
+/// personal data.
+#[cfg(not(feature = "debug-plaintext"))]
+#[derive(Clone, Copy)]
+pub(crate) struct Redacted;
+
+#[cfg(not(feature = "debug-plaintext"))]
+impl std::fmt::Debug for Redacted {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.write_str("<redacted>")
+  }
+}
+
 #[cfg(test)]
 mod test {
   use super::Disclosure;
+  use crate::Error;
+
+  #[cfg(feature = "zeroize")]
+  #[test]
+  fn zeroize() {
+    use zeroize::Zeroize;
+
+    let mut disclosure = Disclosure::new(
+      "2GLC42sKQveCfGfryNRN9w".to_string(),
+      Some("address".to_owned()),
+      serde_json::json!({ "country": "DE", "locality": ["Berlin"] }),
+    )
+    .unwrap();
+    disclosure.zeroize();
+    assert!(disclosure.salt.is_empty());
+    assert_eq!(disclosure.claim_name, None);
+    assert_eq!(disclosure.claim_value, serde_json::Value::Null);
+    assert!(disclosure.disclosure.is_empty());
+    // Disclosures can be wrapped in `Zeroizing`.
+    let _ = zeroize::Zeroizing::new(disclosure);
+  }
 
   // Test values from:
   // https://www.ietf.org/archive/id/draft-ietf-oauth-selective-disclosure-jwt-07.html#appendix-A.2-7
@@ -147,7 +308,8 @@ mod test {
       "2GLC42sKQveCfGfryNRN9w".to_string(),
       Some("time".to_owned()),
       "2012-04-23T18:25Z".to_owned().into(),
-    );
+    )
+    .unwrap();
 
     let parsed =
       Disclosure::parse("WyIyR0xDNDJzS1F2ZUNmR2ZyeU5STjl3IiwgInRpbWUiLCAiMjAxMi0wNC0yM1QxODoyNVoiXQ".to_owned());
@@ -158,10 +320,53 @@ mod test {
   // https://www.ietf.org/archive/id/draft-ietf-oauth-selective-disclosure-jwt-05.html#section-5.5-25
   #[test]
   fn test_creating() {
-    let disclosure = Disclosure::new("lklxF5jMYlGTPUovMNIvCA".to_owned(), None, "US".to_owned().into());
+    let disclosure = Disclosure::new("lklxF5jMYlGTPUovMNIvCA".to_owned(), None, "US".to_owned().into()).unwrap();
     assert_eq!(
       "WyJsa2x4RjVqTVlsR1RQVW92TU5JdkNBIiwgIlVTIl0".to_owned(),
       disclosure.to_string()
     );
   }
+
+  #[cfg(not(feature = "debug-plaintext"))]
+  #[test]
+  fn test_debug_redacted() {
+    let disclosure = Disclosure::new(
+      "2GLC42sKQveCfGfryNRN9w".to_string(),
+      Some("time".to_owned()),
+      "2012-04-23T18:25Z".to_owned().into(),
+    )
+    .unwrap();
+    let debug = format!("{:?}", disclosure);
+    assert!(debug.contains("time"));
+    assert!(!debug.contains("2012-04-23T18:25Z"));
+    assert!(!debug.contains(&disclosure.salt));
+    assert!(!debug.contains(disclosure.as_str()));
+  }
+
+  #[test]
+  fn salt_size() {
+    assert!(matches!(
+      Disclosure::new("c2FsdA".to_owned(), None, "US".into()).unwrap_err(),
+      Error::SaltTooShort { size: 4, min_size: 16 }
+    ));
+    assert!(matches!(
+      Disclosure::new_canonical("6qMQvRL5haj".to_owned(), None, "US".into()).unwrap_err(),
+      Error::SaltTooShort { size: 8, .. }
+    ));
+    assert!(Disclosure::new("lklxF5jMYlGTPUovMNIvCA".to_owned(), None, "US".into()).is_ok());
+    // ["c2FsdA", "US"]
+    let legacy = "WyJjMkZzZEEiLCAiVVMiXQ".to_owned();
+    assert!(matches!(
+      Disclosure::parse(legacy.clone()).unwrap_err(),
+      Error::SaltTooShort { .. }
+    ));
+    assert_eq!(
+      Disclosure::parse_with_min_salt_size(legacy.clone(), 0)
+        .unwrap()
+        .claim_value,
+      "US"
+    );
+    let recreated = Disclosure::new_with_min_salt_size("c2FsdA".to_owned(), None, "US".into(), 0).unwrap();
+    assert_eq!(recreated.into_string(), legacy);
+  }
 }
@@ -1,26 +1,46 @@
 // Copyright 2020-2024 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
+use super::ClaimViolation;
 use super::Disclosure;
 use super::Hasher;
+use super::IssuanceTrace;
 #[cfg(feature = "sha")]
 use super::Sha256Hasher;
+use super::TracedDisclosure;
+use super::MIN_SALT_SIZE;
+use crate::hasher::encoded_digests;
+use crate::pointer::join_pointer;
+use crate::pointer::parse_pointer;
+use crate::pointer::pointer_segments;
 use crate::Error;
 use crate::Result;
-use json_pointer::JsonPointer;
 use rand::Rng;
 use serde_json::json;
 use serde_json::Map;
 use serde_json::Value;
+use std::collections::BTreeMap;
+use std::ops::Range;
 
-pub(crate) const DIGESTS_KEY: &str = "_sd";
-pub(crate) const ARRAY_DIGEST_KEY: &str = "...";
-pub(crate) const DEFAULT_SALT_SIZE: usize = 30;
-pub(crate) const SD_ALG: &str = "_sd_alg";
+/// Name of the property holding the digests of an object's selectively disclosable properties.
+pub const DIGESTS_KEY: &str = "_sd";
+/// Name of the only property of an object replacing a selectively disclosable array element.
+pub const ARRAY_DIGEST_KEY: &str = "...";
+/// Default size of the random data used to generate salts, in bytes.
+pub const DEFAULT_SALT_SIZE: usize = 30;
+/// Name of the property holding the hash algorithm of the digests.
+pub const SD_ALG: &str = "_sd_alg";
+/// `typ` header value of the issuer-signed JWT of a plain SD-JWT, see [`Typ`](crate::Typ) for other profiles.
 pub const HEADER_TYP: &str = "sd-jwt";
 
 /// Transforms a JSON object into an SD-JWT object by substituting selected values
 /// with their corresponding disclosure digests.
+///
+/// ## Note
+///
+/// The resulting [`SdObjectEncoder::object`] is meant to be used as the claims of the issuer-signed JWT,
+/// signed by any JOSE implementation. The disclosures returned by [`SdObjectEncoder::conceal`] are
+/// appended to that JWT, see [`SdJwt`](crate::SdJwt).
 #[cfg(not(feature = "sha"))]
 pub struct SdObjectEncoder<H: Hasher> {
   /// The object in JSON format.
@@ -30,10 +50,22 @@ pub struct SdObjectEncoder<H: Hasher> {
   pub(crate) salt_size: usize,
   /// The hash function used to create digests.
   pub(crate) hasher: H,
+  /// The paths concealed so far and their disclosures.
+  pub(crate) concealed: Vec<(String, Disclosure)>,
+  /// The paths decoys were added to and their digests.
+  pub(crate) decoys: Vec<(String, String)>,
+  /// Whether disclosures and the object are serialized using JCS.
+  pub(crate) canonical: bool,
 }
 
 /// Transforms a JSON object into an SD-JWT object by substituting selected values
 /// with their corresponding disclosure digests.
+///
+/// ## Note
+///
+/// The resulting [`SdObjectEncoder::object`] is meant to be used as the claims of the issuer-signed JWT,
+/// signed by any JOSE implementation. The disclosures returned by [`SdObjectEncoder::conceal`] are
+/// appended to that JWT, see [`SdJwt`](crate::SdJwt).
 #[cfg(feature = "sha")]
 #[derive(Debug, Clone)]
 pub struct SdObjectEncoder<H: Hasher = Sha256Hasher> {
@@ -44,6 +76,12 @@ pub struct SdObjectEncoder<H: Hasher = Sha256Hasher> {
   pub(crate) salt_size: usize,
   /// The hash function used to create digests.
   pub(crate) hasher: H,
+  /// The paths concealed so far and their disclosures.
+  pub(crate) concealed: Vec<(String, Disclosure)>,
+  /// The paths decoys were added to and their digests.
+  pub(crate) decoys: Vec<(String, String)>,
+  /// Whether disclosures and the object are serialized using JCS.
+  pub(crate) canonical: bool,
 }
 
 #[cfg(feature = "sha")]
@@ -55,13 +93,19 @@ impl SdObjectEncoder {
   pub fn new(object: &str) -> Result<SdObjectEncoder<Sha256Hasher>> {
     let object: Value = serde_json::from_str(object).map_err(|e| Error::DeserializationError(e.to_string()))?;
     if !object.is_object() {
-      return Err(Error::DataTypeMismatch("expected object".to_owned()));
+      return Err(Error::DataTypeMismatch {
+        path: String::new(),
+        reason: "expected object".to_owned(),
+      });
     }
 
     Ok(SdObjectEncoder {
       object,
       salt_size: DEFAULT_SALT_SIZE,
       hasher: Sha256Hasher::new(),
+      concealed: vec![],
+      decoys: vec![],
+      canonical: false,
     })
   }
 
@@ -80,28 +124,52 @@ impl TryFrom<Value> for SdObjectEncoder {
   type Error = crate::Error;
   fn try_from(value: Value) -> std::result::Result<Self, Self::Error> {
     if !value.is_object() {
-      return Err(Error::DataTypeMismatch("expected object".to_owned()));
+      return Err(Error::DataTypeMismatch {
+        path: String::new(),
+        reason: "expected object".to_owned(),
+      });
     }
 
     Ok(SdObjectEncoder {
       object: value,
       salt_size: DEFAULT_SALT_SIZE,
       hasher: Sha256Hasher::new(),
+      concealed: vec![],
+      decoys: vec![],
+      canonical: false,
     })
   }
 }
 
 impl<H: Hasher> SdObjectEncoder<H> {
   /// Creates a new [`SdObjectEncoder`] with custom hash function to create digests.
+  ///
+  /// ## Error
+  /// Returns [`Error::DeserializationError`] if `object` is not a valid JSON object.
   pub fn with_custom_hasher(object: &str, hasher: H) -> Result<Self> {
-    let object: Value = serde_json::to_value(object).map_err(|e| Error::DeserializationError(e.to_string()))?;
+    let object: Value = serde_json::from_str(object).map_err(|e| Error::DeserializationError(e.to_string()))?;
+    Self::from_value_with_hasher(object, hasher)
+  }
+
+  /// Creates a new [`SdObjectEncoder`] with custom hash function, taking ownership of `object` without
+  /// copying it.
+  ///
+  /// ## Error
+  /// Returns [`Error::DataTypeMismatch`] if `object` is not a JSON object.
+  pub fn from_value_with_hasher(object: Value, hasher: H) -> Result<Self> {
     if !object.is_object() {
-      return Err(Error::DataTypeMismatch("expected object".to_owned()));
+      return Err(Error::DataTypeMismatch {
+        path: String::new(),
+        reason: "expected object".to_owned(),
+      });
     }
     Ok(Self {
       object,
       salt_size: DEFAULT_SALT_SIZE,
       hasher,
+      concealed: vec![],
+      decoys: vec![],
+      canonical: false,
     })
   }
 
@@ -129,65 +197,313 @@ impl<H: Hasher> SdObjectEncoder<H> {
   /// encoder.conceal("/claim1/abc", None).unwrap(); //"abc": true
   /// encoder.conceal("/claim2/0", None).unwrap(); //conceals "val_1"
   /// ```
-  /// 
+  ///
   /// ## Error
   /// * [`Error::InvalidPath`] if pointer is invalid.
+  /// * [`Error::AlreadyConcealed`] if the value at `path` was concealed before.
   /// * [`Error::DataTypeMismatch`] if existing SD format is invalid.
+  /// * [`Error::SaltTooShort`] or [`Error::InvalidDisclosure`] if `salt` is shorter than
+  ///   [`MIN_SALT_SIZE`](crate::MIN_SALT_SIZE) bytes.
   pub fn conceal(&mut self, path: &str, salt: Option<String>) -> Result<Disclosure> {
+    if self.concealed.iter().any(|(concealed, _)| concealed == path) {
+      return Err(Error::AlreadyConcealed { path: path.to_owned() });
+    }
+    let disclosure = self.conceal_value(path, salt)?;
+    self.concealed.push((path.to_owned(), disclosure.clone()));
+    Ok(disclosure)
+  }
+
+  /// Conceals the elements at the indices in `range` of the array at `path` as separate disclosures,
+  /// returned in the order of the indices.
+  ///
+  /// Concealed elements are replaced by their digests in place, so the indices of the remaining elements
+  /// do not shift.
+  ///
+  /// ## Error
+  /// * [`Error::InvalidPath`] if `path` is invalid, does not point to an array or `range` exceeds its length.
+  /// * [`Error::AlreadyConcealed`] if one of the elements was concealed before.
+  /// * [`Error::InvalidPath`] if one of the elements is a decoy digest, see [`SdObjectEncoder::add_decoys`].
+  ///
+  /// Nothing is concealed if an error is returned.
+  pub fn conceal_range(&mut self, path: &str, range: Range<usize>) -> Result<Vec<Disclosure>> {
+    let array = parse_pointer(path)?
+      .get(&self.object)
+      .ok()
+      .and_then(Value::as_array)
+      .ok_or_else(|| invalid_path(path, "path does not point to an array"))?;
+    if range.end > array.len() {
+      return Err(invalid_path(
+        path,
+        format!("range {:?} exceeds the array length {}", range, array.len()),
+      ));
+    }
+    let paths: Vec<String> = range
+      .clone()
+      .map(|index| join_pointer(path, &index.to_string()))
+      .collect();
+    if let Some((element_path, _)) = paths
+      .iter()
+      .zip(&array[range])
+      .find(|(element_path, element)| self.is_decoy(element_path, element))
+    {
+      return Err(invalid_path(element_path, "element is a decoy digest"));
+    }
+    self.conceal_paths(&paths)
+  }
+
+  /// Conceals every element of an array or every property of an object as separate disclosures, selected
+  /// by `path` ending with a wildcard segment, e.g. `/nationalities/*`.
+  ///
+  /// The disclosures are returned in the order of the elements or properties. `_sd`, `_sd_alg` and decoy
+  /// digests of an array are not concealed. To conceal a property named `*`, use [`SdObjectEncoder::conceal`].
+  ///
+  /// ## Error
+  /// * [`Error::InvalidPath`] if `path` does not end with `/*` or the rest of it does not point to an array
+  ///   or object.
+  /// * [`Error::AlreadyConcealed`] if one of the values was concealed before.
+  ///
+  /// Nothing is concealed if an error is returned.
+  pub fn conceal_wildcard(&mut self, path: &str) -> Result<Vec<Disclosure>> {
+    let parent = path
+      .strip_suffix("/*")
+      .ok_or_else(|| invalid_path(path, "path does not end with a wildcard"))?;
+    let paths: Vec<String> = match parse_pointer(parent)?.get(&self.object) {
+      Ok(Value::Array(array)) => array
+        .iter()
+        .enumerate()
+        .map(|(index, element)| (join_pointer(parent, &index.to_string()), element))
+        .filter(|(path, element)| !self.is_decoy(path, element))
+        .map(|(path, _)| path)
+        .collect(),
+      Ok(Value::Object(object)) => object
+        .keys()
+        .filter(|key| *key != DIGESTS_KEY && *key != SD_ALG)
+        .map(|key| join_pointer(parent, key))
+        .collect(),
+      _ => return Err(invalid_path(path, "wildcard does not follow an array or object")),
+    };
+    self.conceal_paths(&paths)
+  }
+
+  /// Conceals all `paths` with random salts, after checking that none of them was concealed before.
+  ///
+  /// The digests of all disclosures are computed at once, in parallel with the `parallel` feature.
+  fn conceal_paths(&mut self, paths: &[String]) -> Result<Vec<Disclosure>> {
+    if let Some(path) = paths
+      .iter()
+      .find(|path| self.concealed.iter().any(|(concealed, _)| concealed == *path))
+    {
+      return Err(Error::AlreadyConcealed { path: path.clone() });
+    }
+    // No value is taken out of the object before all digests are known to be insertable.
+    paths.iter().try_for_each(|path| self.check_conceal_target(path))?;
+    let mut disclosures = Vec::with_capacity(paths.len());
+    for path in paths {
+      disclosures.push(self.take_value(path, None)?);
+    }
+    let encoded: Vec<String> = disclosures.iter().map(ToString::to_string).collect();
+    for ((path, disclosure), digest) in paths
+      .iter()
+      .zip(&disclosures)
+      .zip(encoded_digests(&self.hasher, &encoded))
+    {
+      self.insert_digest(path, digest)?;
+      self.concealed.push((path.clone(), disclosure.clone()));
+    }
+    Ok(disclosures)
+  }
+
+  /// Returns whether the array element `value` at `path` is the digest of a decoy or of a value not concealed
+  /// by this encoder, which must not be wrapped in another disclosure.
+  fn is_decoy(&self, path: &str, value: &Value) -> bool {
+    let is_digest = value.as_object().map_or(false, |object| {
+      object.len() == 1 && object.contains_key(ARRAY_DIGEST_KEY)
+    });
+    is_digest && !self.concealed.iter().any(|(concealed, _)| concealed == path)
+  }
+
+  /// Checks that the value at `path` can be taken out by [`SdObjectEncoder::take_value`] and its digest
+  /// inserted by [`SdObjectEncoder::insert_digest`], without modifying the object.
+  fn check_conceal_target(&self, path: &str) -> Result<()> {
+    let mut parent_pointer = parse_pointer(path)?;
+    let element_key = parent_pointer
+      .pop()
+      .ok_or_else(|| invalid_path(path, "path does not contain any values"))?;
+    let parent = parent_pointer
+      .get(&self.object)
+      .map_err(|err| invalid_path(path, format!("{:?}", err)))?;
+    let exists = match parent {
+      Value::Object(object) => {
+        if object.get(DIGESTS_KEY).map_or(false, |digests| !digests.is_array()) {
+          return Err(Error::DataTypeMismatch {
+            path: join_pointer(&parent_pointer.to_string(), DIGESTS_KEY),
+            reason: "invalid object: existing `_sd` type is not an array".to_string(),
+          });
+        }
+        object.contains_key(&element_key)
+      }
+      Value::Array(array) => element_key.parse::<usize>().map_or(false, |index| index < array.len()),
+      _ => {
+        return Err(invalid_path(
+          path,
+          "parent of element can can only be an object or an array",
+        ))
+      }
+    };
+    if !exists {
+      return Err(invalid_path(path, format!("{} does not exist", element_key)));
+    }
+    Ok(())
+  }
+
+  fn conceal_value(&mut self, path: &str, salt: Option<String>) -> Result<Disclosure> {
+    self.check_conceal_target(path)?;
+    let disclosure = self.take_value(path, salt)?;
+    let digest = self.hasher.encoded_digest(disclosure.as_str());
+    self.insert_digest(path, digest)?;
+    Ok(disclosure)
+  }
+
+  /// Takes the value at `path` out of the object and creates its disclosure. An array element is replaced by
+  /// `null` until its digest is inserted with [`SdObjectEncoder::insert_digest`].
+  fn take_value(&mut self, path: &str, salt: Option<String>) -> Result<Disclosure> {
     // Determine salt.
     let salt = salt.unwrap_or(Self::gen_rand(self.salt_size));
+    // Checked before the value is taken out of the object.
+    Disclosure::validate_salt(&salt, MIN_SALT_SIZE)?;
 
-    let element_pointer = path
-      .parse::<JsonPointer<_, _>>()
-      .map_err(|err| Error::InvalidPath(format!("{:?}", err)))?;
+    let element_pointer = parse_pointer(path)?;
 
     let mut parent_pointer = element_pointer.clone();
     let element_key = parent_pointer
       .pop()
-      .ok_or(Error::InvalidPath("path does not contain any values".to_string()))?;
+      .ok_or_else(|| invalid_path(path, "path does not contain any values"))?;
 
     let parent = parent_pointer
       .get(&self.object)
-      .map_err(|err| Error::InvalidPath(format!("{:?}", err)))?;
+      .map_err(|err| invalid_path(path, format!("{:?}", err)))?;
 
     match parent {
       Value::Object(_) => {
         let parent = parent_pointer
           .get_mut(&mut self.object)
-          .map_err(|err| Error::InvalidPath(format!("{:?}", err)))?
+          .map_err(|err| invalid_path(path, format!("{:?}", err)))?
           .as_object_mut()
-          .ok_or(Error::InvalidPath("path does not contain any values".to_string()))?;
+          .ok_or_else(|| invalid_path(path, "path does not contain any values"))?;
 
         // Remove the value from the parent and create a disclosure for it.
-        let disclosure = Disclosure::new(
-          salt,
-          Some(element_key.to_owned()),
-          parent
-            .remove(&element_key)
-            .ok_or(Error::InvalidPath(format!("{} does not exist", element_key)))?,
-        );
-
-        // Hash the disclosure.
-        let hash = self.hasher.encoded_digest(disclosure.as_str());
-
-        // Add the hash to the "_sd" array if exists; otherwise, create the array and insert the hash.
-        Self::add_digest_to_object(parent, hash)?;
-        Ok(disclosure)
+        let value = remove_property(parent, &element_key)
+          .ok_or_else(|| invalid_path(path, format!("{} does not exist", element_key)))?;
+        new_disclosure(self.canonical, salt, Some(element_key.to_owned()), value)
       }
       Value::Array(_) => {
-        let element = element_pointer.get_mut(&mut self.object).unwrap();
-        let disclosure = Disclosure::new(salt, None, element.clone());
-        let hash = self.hasher.encoded_digest(disclosure.as_str());
-        let tripledot = json!({ARRAY_DIGEST_KEY: hash});
-        *element = tripledot;
-        Ok(disclosure)
+        let element = element_pointer
+          .get_mut(&mut self.object)
+          .map_err(|err| invalid_path(path, format!("{:?}", err)))?;
+        new_disclosure(self.canonical, salt, None, std::mem::take(element))
+      }
+      _ => Err(invalid_path(
+        path,
+        "parent of element can can only be an object or an array",
+      )),
+    }
+  }
+
+  /// Inserts the `digest` of the value taken out at `path` by [`SdObjectEncoder::take_value`].
+  fn insert_digest(&mut self, path: &str, digest: String) -> Result<()> {
+    let mut parent_pointer = parse_pointer(path)?;
+    let element_key = parent_pointer
+      .pop()
+      .ok_or_else(|| invalid_path(path, "path does not contain any values"))?;
+    let parent = parent_pointer
+      .get_mut(&mut self.object)
+      .map_err(|err| invalid_path(path, format!("{:?}", err)))?;
+    match parent {
+      // Add the hash to the "_sd" array if exists; otherwise, create the array and insert the hash.
+      Value::Object(parent) => Self::add_digest_to_object(parent, parent_pointer.to_string().as_str(), digest),
+      Value::Array(array) => {
+        let element = element_key
+          .parse::<usize>()
+          .ok()
+          .and_then(|index| array.get_mut(index))
+          .ok_or_else(|| invalid_path(path, format!("{} does not exist", element_key)))?;
+        *element = json!({ARRAY_DIGEST_KEY: digest});
+        Ok(())
       }
-      _ => Err(crate::Error::Unspecified(
-        "parent of element can can only be an object or an array".to_string(),
+      _ => Err(invalid_path(
+        path,
+        "parent of element can can only be an object or an array",
       )),
     }
   }
 
+  /// Reverts [`SdObjectEncoder::conceal`] for `path`: the digest is removed and the plain text value is
+  /// restored. Returns the disclosure of the value, which must not be issued anymore.
+  ///
+  /// Values concealed inside another concealed value can only be revealed after that value.
+  ///
+  /// ## Error
+  /// * [`Error::InvalidPath`] if the value at `path` was not concealed by this encoder or is part of
+  ///   another concealed value.
+  /// * [`Error::ClaimCollisionError`] if a property with the same name was added in the meantime.
+  /// * [`Error::DataTypeMismatch`] if the digest was removed from the object in the meantime.
+  pub fn reveal(&mut self, path: &str) -> Result<Disclosure> {
+    let index = self
+      .concealed
+      .iter()
+      .position(|(concealed, _)| concealed == path)
+      .ok_or_else(|| invalid_path(path, "value was not concealed"))?;
+    let disclosure = &self.concealed[index].1;
+    let digest = Value::String(self.hasher.encoded_digest(disclosure.as_str()));
+
+    let mut parent_pointer = parse_pointer(path)?;
+    let element_key = parent_pointer
+      .pop()
+      .ok_or_else(|| invalid_path(path, "path does not contain any values"))?;
+    let parent = parent_pointer
+      .get_mut(&mut self.object)
+      .map_err(|_| invalid_path(path, "value is part of another concealed value"))?;
+    let digest_not_found = || Error::DataTypeMismatch {
+      path: path.to_owned(),
+      reason: "digest of the disclosure not found".to_string(),
+    };
+
+    match (parent, &disclosure.claim_name) {
+      (Value::Object(parent), Some(claim_name)) => {
+        if parent.contains_key(claim_name) {
+          return Err(Error::ClaimCollisionError {
+            path: parent_pointer.to_string(),
+            claim_name: claim_name.clone(),
+          });
+        }
+        let digests = parent
+          .get_mut(DIGESTS_KEY)
+          .and_then(Value::as_array_mut)
+          .ok_or_else(digest_not_found)?;
+        let position = digests
+          .iter()
+          .position(|value| *value == digest)
+          .ok_or_else(digest_not_found)?;
+        digests.remove(position);
+        if digests.is_empty() {
+          remove_property(parent, DIGESTS_KEY);
+        }
+        parent.insert(claim_name.clone(), disclosure.claim_value.clone());
+      }
+      (Value::Array(parent), None) => {
+        let element = element_key
+          .parse::<usize>()
+          .ok()
+          .and_then(|index| parent.get_mut(index))
+          .filter(|element| element.get(ARRAY_DIGEST_KEY) == Some(&digest))
+          .ok_or_else(digest_not_found)?;
+        *element = disclosure.claim_value.clone();
+      }
+      _ => return Err(digest_not_found()),
+    }
+    Ok(self.concealed.remove(index).1)
+  }
+
   /// Adds the `_sd_alg` property to the top level of the object.
   /// The value is taken from the [`crate::Hasher::alg_name`] implementation.
   pub fn add_sd_alg_property(&mut self) -> Option<Value> {
@@ -199,7 +515,12 @@ impl<H: Hasher> SdObjectEncoder<H> {
   }
 
   /// Returns the modified object as a string.
+  ///
+  /// The object is serialized using JCS if enabled with [`SdObjectEncoder::set_canonical`].
   pub fn try_to_string(&self) -> Result<String> {
+    if self.canonical {
+      return Ok(crate::canonicalize(&self.object));
+    }
     serde_json::to_string(&self.object)
       .map_err(|_e| Error::Unspecified("error while serializing internal object".to_string()))
   }
@@ -210,47 +531,165 @@ impl<H: Hasher> SdObjectEncoder<H> {
   /// [JSON pointer](https://datatracker.ietf.org/doc/html/rfc6901).
   ///
   /// Use `path` = "" to add decoys to the top level.
+  ///
+  /// `path` may point into a concealed value, e.g. `/address` after `/address/country` and `/address` were
+  /// concealed, so that nested levels do not reveal their number of claims. The disclosures of the
+  /// enclosing concealed values are then recreated with the same salts, replacing the disclosures returned
+  /// before, see [`SdObjectEncoder::disclosures`].
+  ///
+  /// ## Error
+  /// The object and its disclosures are left unchanged if an error is returned.
   pub fn add_decoys(&mut self, path: &str, number_of_decoys: usize) -> Result<()> {
-    for _ in 0..number_of_decoys {
-      self.add_decoy(path)?;
+    self.update_nested(path, |encoder| {
+      (0..number_of_decoys).try_for_each(|_| encoder.add_decoy(path).map(|_| ()))
+    })
+  }
+
+  /// Applies `update` to the value at `path`. If `path` points into concealed values, these are revealed
+  /// first, outermost first, and concealed again with the same salts afterwards.
+  fn update_nested<F>(&mut self, path: &str, update: F) -> Result<()>
+  where
+    F: FnOnce(&mut Self) -> Result<()>,
+  {
+    let segments = pointer_segments(path)?;
+    let mut enclosing: Vec<String> = self
+      .concealed
+      .iter()
+      .map(|(concealed, _)| concealed.clone())
+      .filter(|concealed| pointer_segments(concealed).map_or(false, |concealed| segments.starts_with(&concealed)))
+      .collect();
+    // Nested values can only be revealed after the values enclosing them.
+    enclosing.sort_by_key(|concealed| pointer_segments(concealed).map_or(0, |segments| segments.len()));
+
+    if enclosing.is_empty() {
+      return update(self);
+    }
+
+    // Restore the enclosing values if revealing, updating or concealing again fails midway.
+    let object = self.object.clone();
+    let concealed = self.concealed.clone();
+    let decoys = self.decoys.len();
+    let result = self.update_revealed(&enclosing, update);
+    if result.is_err() {
+      self.object = object;
+      self.concealed = concealed;
+      self.decoys.truncate(decoys);
+    }
+    result
+  }
+
+  /// Reveals the `enclosing` concealed values, outermost first, applies `update` and conceals the values
+  /// again with the same salts.
+  fn update_revealed<F>(&mut self, enclosing: &[String], update: F) -> Result<()>
+  where
+    F: FnOnce(&mut Self) -> Result<()>,
+  {
+    let mut revealed = Vec::with_capacity(enclosing.len());
+    for concealed in enclosing {
+      let disclosure = self.reveal(concealed)?;
+      revealed.push((concealed, disclosure));
+    }
+    update(self)?;
+    for (concealed, disclosure) in revealed.into_iter().rev() {
+      self.conceal(concealed, Some(disclosure.salt.clone()))?;
+    }
+    Ok(())
+  }
+
+  /// Adds the number of decoy digests given for each path of `decoys`, e.g. as specified by an issuance
+  /// template, see [`SdObjectEncoder::add_decoys`].
+  ///
+  /// ## Error
+  /// Returns the errors of all paths decoys can not be added to, see [`Error::path`]. No decoys are added
+  /// if an error is returned.
+  pub fn add_decoys_map<'a, I>(&mut self, decoys: I) -> std::result::Result<(), Vec<Error>>
+  where
+    I: IntoIterator<Item = (&'a str, usize)>,
+  {
+    let decoys_len = self.decoys.len();
+    let mut errors: Vec<Error> = decoys
+      .into_iter()
+      .filter_map(|(path, number_of_decoys)| self.add_decoys(path, number_of_decoys).err())
+      .collect();
+    if errors.is_empty() {
+      return Ok(());
+    }
+
+    // Remove the decoys added to the other paths again, touching only the arrays they were added to.
+    let mut added: Vec<(String, Vec<String>)> = vec![];
+    for (path, digest) in self.decoys.drain(decoys_len..) {
+      match added.iter_mut().find(|(added_path, _)| *added_path == path) {
+        Some((_, digests)) => digests.push(digest),
+        None => added.push((path, vec![digest])),
+      }
+    }
+    for (path, digests) in added.into_iter().rev() {
+      if let Err(err) = self.update_nested(&path, |encoder| encoder.remove_decoys(&path, &digests)) {
+        errors.push(err);
+      }
+    }
+    Err(errors)
+  }
+
+  /// Removes the decoy `digests` from the `_sd` array of the object or from the array at `path`.
+  fn remove_decoys(&mut self, path: &str, digests: &[String]) -> Result<()> {
+    let is_decoy = |digest: Option<&Value>| {
+      digest
+        .and_then(Value::as_str)
+        .map_or(false, |digest| digests.iter().any(|decoy| decoy == digest))
+    };
+    let value = parse_pointer(path)?
+      .get_mut(&mut self.object)
+      .map_err(|err| invalid_path(path, format!("{:?}", err)))?;
+    match value {
+      Value::Object(object) => {
+        if let Some(sd) = object.get_mut(DIGESTS_KEY).and_then(Value::as_array_mut) {
+          sd.retain(|digest| !is_decoy(Some(digest)));
+          if sd.is_empty() {
+            remove_property(object, DIGESTS_KEY);
+          }
+        }
+      }
+      Value::Array(array) => array.retain(|element| !is_decoy(element.get(ARRAY_DIGEST_KEY))),
+      _ => return Err(invalid_path(path, "value is neither an object nor an array")),
     }
     Ok(())
   }
 
   fn add_decoy(&mut self, path: &str) -> Result<Disclosure> {
-    let mut element_pointer = path
-      .parse::<JsonPointer<_, _>>()
-      .map_err(|err| Error::InvalidPath(format!("{:?}", err)))?;
+    let element_pointer = parse_pointer(path)?;
 
     let value = element_pointer
       .get_mut(&mut self.object)
-      .map_err(|err| Error::InvalidPath(format!("{:?}", err)))?;
+      .map_err(|err| invalid_path(path, format!("{:?}", err)))?;
     if let Some(object) = value.as_object_mut() {
-      let (disclosure, hash) = Self::random_digest(&self.hasher, self.salt_size, true);
-      Self::add_digest_to_object(object, hash)?;
+      let (disclosure, hash) = Self::random_digest(&self.hasher, self.salt_size, true)?;
+      Self::add_digest_to_object(object, path, hash.clone())?;
+      self.decoys.push((path.to_owned(), hash));
       Ok(disclosure)
     } else if let Some(array) = value.as_array_mut() {
-      let (disclosure, hash) = Self::random_digest(&self.hasher, self.salt_size, true);
+      let (disclosure, hash) = Self::random_digest(&self.hasher, self.salt_size, true)?;
       let tripledot = json!({ARRAY_DIGEST_KEY: hash});
       array.push(tripledot);
+      self.decoys.push((path.to_owned(), hash));
       Ok(disclosure)
     } else {
-      Err(Error::InvalidPath(format!(
-        "{:?} is neither an object nor an array",
-        element_pointer.pop()
-      )))
+      Err(invalid_path(path, "value is neither an object nor an array"))
     }
   }
 
   /// Add the hash to the "_sd" array if exists; otherwise, create the array and insert the hash.
-  fn add_digest_to_object(object: &mut Map<String, Value>, digest: String) -> Result<()> {
+  ///
+  /// `path` is the pointer to `object` used for error reporting.
+  fn add_digest_to_object(object: &mut Map<String, Value>, path: &str, digest: String) -> Result<()> {
     if let Some(sd_value) = object.get_mut(DIGESTS_KEY) {
       if let Value::Array(value) = sd_value {
         value.push(Value::String(digest))
       } else {
-        return Err(Error::DataTypeMismatch(
-          "invalid object: existing `_sd` type is not an array".to_string(),
-        ));
+        return Err(Error::DataTypeMismatch {
+          path: join_pointer(path, DIGESTS_KEY),
+          reason: "invalid object: existing `_sd` type is not an array".to_string(),
+        });
       }
     } else {
       object.insert(DIGESTS_KEY.to_owned(), Value::Array(vec![Value::String(digest)]));
@@ -258,7 +697,7 @@ impl<H: Hasher> SdObjectEncoder<H> {
     Ok(())
   }
 
-  fn random_digest(hasher: &dyn Hasher, salt_len: usize, array_entry: bool) -> (Disclosure, String) {
+  fn random_digest(hasher: &dyn Hasher, salt_len: usize, array_entry: bool) -> Result<(Disclosure, String)> {
     let mut rng = rand::thread_rng();
     let salt = Self::gen_rand(salt_len);
     let decoy_value_length = rng.gen_range(20..=100);
@@ -269,9 +708,9 @@ impl<H: Hasher> SdObjectEncoder<H> {
       Some(Self::gen_rand(decoy_claim_name_length))
     };
     let decoy_value = Self::gen_rand(decoy_value_length);
-    let disclosure = Disclosure::new(salt, decoy_claim_name, Value::String(decoy_value));
+    let disclosure = Disclosure::new(salt, decoy_claim_name, Value::String(decoy_value))?;
     let hash = hasher.encoded_digest(disclosure.as_str());
-    (disclosure, hash)
+    Ok((disclosure, hash))
   }
 
   fn gen_rand(len: usize) -> String {
@@ -279,15 +718,86 @@ impl<H: Hasher> SdObjectEncoder<H> {
     let mut rng = rand::thread_rng();
     rng.fill(&mut bytes[..]);
 
-    multibase::Base::Base64Url.encode(bytes)
+    let encoded = crate::base64::encode(&bytes);
+    #[cfg(feature = "zeroize")]
+    zeroize::Zeroize::zeroize(&mut bytes);
+    encoded
   }
 
   /// Returns a reference to the internal object.
   pub fn object(&self) -> Result<&Map<String, Value>> {
     // Safety: encoder can be constructed from objects only.
-    self.object.as_object().ok_or(Error::DataTypeMismatch(
-      "encoder initialized with invalid JSON object".to_string(),
-    ))
+    self.object.as_object().ok_or_else(|| Error::DataTypeMismatch {
+      path: String::new(),
+      reason: "encoder initialized with invalid JSON object".to_string(),
+    })
+  }
+
+  /// Returns the modified object, consuming the encoder without copying the object.
+  pub fn into_object(self) -> Map<String, Value> {
+    match self.object {
+      Value::Object(object) => object,
+      // Unreachable since the encoder can be constructed from objects only.
+      _ => Map::new(),
+    }
+  }
+
+  /// Checks the registered claims of the object for basic coherence, e.g. that `exp` is after `iat`, and
+  /// returns all violations, see [`validate_claims`](crate::validate_claims).
+  ///
+  /// Meant to be called before the object is signed, claims concealed so far are not checked.
+  pub fn validate_claims(&self, key_binding_required: bool) -> Vec<ClaimViolation> {
+    self
+      .object
+      .as_object()
+      .map(|object| crate::validate_claims(object, key_binding_required))
+      .unwrap_or_default()
+  }
+
+  /// Returns the disclosures of the values concealed so far, in the order they were concealed.
+  pub fn disclosures(&self) -> impl Iterator<Item = &Disclosure> {
+    self.concealed.iter().map(|(_, disclosure)| disclosure)
+  }
+
+  /// Returns the [`IssuanceTrace`] of the values concealed and the decoys added so far.
+  ///
+  /// Values revealed with [`SdObjectEncoder::reveal`] are not part of the trace.
+  pub fn trace(&self) -> IssuanceTrace {
+    let disclosures = self
+      .concealed
+      .iter()
+      .map(|(path, disclosure)| TracedDisclosure {
+        path: path.clone(),
+        salt: disclosure.salt.clone(),
+        digest: self.hasher.encoded_digest(disclosure.as_str()),
+      })
+      .collect();
+    let mut decoys = BTreeMap::<String, Vec<String>>::new();
+    for (path, digest) in &self.decoys {
+      decoys.entry(path.clone()).or_default().push(digest.clone());
+    }
+    IssuanceTrace {
+      sd_alg: self.hasher.alg_name().to_owned(),
+      disclosures,
+      decoys,
+    }
+  }
+
+  /// Returns whether disclosures and the object are serialized using the
+  /// [JSON Canonicalization Scheme](https://www.rfc-editor.org/rfc/rfc8785).
+  pub fn canonical(&self) -> bool {
+    self.canonical
+  }
+
+  /// Sets whether the disclosures created from now on and [`SdObjectEncoder::try_to_string`] use the
+  /// [JSON Canonicalization Scheme](https://www.rfc-editor.org/rfc/rfc8785), so that tokens generated
+  /// independently from the same content and salts are identical. Disabled by default.
+  ///
+  /// ## Warning
+  /// JCS formats every number as an `f64`, so integers above 2^53, e.g. [`u64::MAX`], are rounded and do not
+  /// survive the round trip unchanged. Encode such values as strings.
+  pub fn set_canonical(&mut self, canonical: bool) {
+    self.canonical = canonical;
   }
 
   /// Returns the used salt length.
@@ -297,11 +807,14 @@ impl<H: Hasher> SdObjectEncoder<H> {
 
   /// Sets size of random data used to generate the salts for disclosures in bytes.
   ///
-  /// ## Warning
-  /// Salt size must be >= 16.
+  /// ## Error
+  /// Returns [`Error::SaltTooShort`] if `salt_size` is smaller than [`MIN_SALT_SIZE`](crate::MIN_SALT_SIZE).
   pub fn set_salt_size(&mut self, salt_size: usize) -> Result<()> {
-    if salt_size < 16 {
-      Err(Error::InvalidSaltSize)
+    if salt_size < MIN_SALT_SIZE {
+      Err(Error::SaltTooShort {
+        size: salt_size,
+        min_size: MIN_SALT_SIZE,
+      })
     } else {
       self.salt_size = salt_size;
       Ok(())
@@ -309,11 +822,43 @@ impl<H: Hasher> SdObjectEncoder<H> {
   }
 }
 
+/// Removes `key` from `object`, keeping the order of the remaining properties if `preserve_order` is enabled.
+pub(crate) fn remove_property(object: &mut Map<String, Value>, key: &str) -> Option<Value> {
+  #[cfg(feature = "preserve_order")]
+  {
+    object.shift_remove(key)
+  }
+  #[cfg(not(feature = "preserve_order"))]
+  {
+    object.remove(key)
+  }
+}
+
+fn new_disclosure(canonical: bool, salt: String, claim_name: Option<String>, claim_value: Value) -> Result<Disclosure> {
+  if canonical {
+    Disclosure::new_canonical(salt, claim_name, claim_value)
+  } else {
+    Disclosure::new(salt, claim_name, claim_value)
+  }
+}
+
+fn invalid_path(path: &str, reason: impl Into<String>) -> Error {
+  Error::InvalidPath {
+    path: path.to_owned(),
+    reason: reason.into(),
+  }
+}
+
 #[cfg(test)]
 mod test {
 
   use super::SdObjectEncoder;
+  use crate::Disclosure;
   use crate::Error;
+  use crate::Hasher;
+  use crate::SdObjectDecoder;
+  use crate::Sha256Hasher;
+  use crate::ARRAY_DIGEST_KEY;
   use serde::Serialize;
   use serde_json::json;
   use serde_json::Value;
@@ -352,8 +897,360 @@ mod test {
     encoder.conceal("/claim1/abc", None).unwrap();
     assert!(matches!(
       encoder.conceal("claim2/2", None).unwrap_err(),
-      Error::InvalidPath(_)
+      Error::InvalidPath { .. }
+    ));
+  }
+
+  #[test]
+  fn already_concealed() {
+    let mut encoder = SdObjectEncoder::try_from(object()).unwrap();
+    encoder.conceal("/id", None).unwrap();
+    encoder.conceal("/claim2/0", None).unwrap();
+    let error = encoder.conceal("/claim2/0", None).unwrap_err();
+    assert!(matches!(error, Error::AlreadyConcealed { .. }));
+    assert_eq!(error.path(), Some("/claim2/0"));
+    assert!(matches!(
+      encoder.conceal("/id", None).unwrap_err(),
+      Error::AlreadyConcealed { .. }
+    ));
+    assert_eq!(encoder.object.get("claim2").unwrap().as_array().unwrap().len(), 2);
+  }
+
+  #[test]
+  fn conceal_range() {
+    let mut encoder = SdObjectEncoder::try_from(json!({ "degrees": ["a", "b", "c", "d", "e"] })).unwrap();
+    encoder.conceal("/degrees/2", None).unwrap();
+    assert!(matches!(
+      encoder.conceal_range("/degrees", 1..4).unwrap_err(),
+      Error::AlreadyConcealed { path } if path == "/degrees/2"
+    ));
+    assert!(matches!(
+      encoder.conceal_range("/degrees", 3..6).unwrap_err(),
+      Error::InvalidPath { .. }
+    ));
+    assert!(encoder.conceal_range("/missing", 0..1).is_err());
+
+    let disclosures = encoder.conceal_range("/degrees", 3..5).unwrap();
+    let values: Vec<&Value> = disclosures.iter().map(|disclosure| &disclosure.claim_value).collect();
+    assert_eq!(values, vec!["d", "e"]);
+    let degrees = encoder.object().unwrap()["degrees"].as_array().unwrap();
+    assert_eq!(degrees.len(), 5);
+    assert_eq!(degrees[1], "b");
+    assert!(degrees[3].get(ARRAY_DIGEST_KEY).is_some());
+
+    // Enough elements to compute the digests in parallel with the `parallel` feature.
+    let courses: Vec<usize> = (0..100).collect();
+    let mut encoder = SdObjectEncoder::try_from(json!({ "courses": courses })).unwrap();
+    let disclosures: Vec<String> = encoder
+      .conceal_range("/courses", 0..100)
+      .unwrap()
+      .into_iter()
+      .map(Disclosure::into_string)
+      .collect();
+    let decoded = SdObjectDecoder::new_with_sha256()
+      .decode(encoder.object().unwrap(), &disclosures)
+      .unwrap();
+    assert_eq!(decoded["courses"], json!(courses));
+  }
+
+  #[test]
+  fn conceal_range_decoys() {
+    let mut encoder = SdObjectEncoder::try_from(json!({ "degrees": ["a", "b"] })).unwrap();
+    encoder.add_decoys("/degrees", 2).unwrap();
+    let object = encoder.object().unwrap().clone();
+    assert!(matches!(
+      encoder.conceal_range("/degrees", 0..4).unwrap_err(),
+      Error::InvalidPath { path, .. } if path == "/degrees/2"
+    ));
+    assert_eq!(encoder.object().unwrap(), &object);
+    assert_eq!(encoder.disclosures().count(), 0);
+
+    let disclosures: Vec<String> = encoder
+      .conceal_range("/degrees", 0..2)
+      .unwrap()
+      .into_iter()
+      .map(Disclosure::into_string)
+      .collect();
+    let decoded = SdObjectDecoder::new_with_sha256()
+      .decode(encoder.object().unwrap(), &disclosures)
+      .unwrap();
+    assert_eq!(decoded["degrees"], json!(["a", "b"]));
+  }
+
+  #[test]
+  fn conceal_wildcard() {
+    let mut encoder = SdObjectEncoder::try_from(json!({
+      "nationalities": ["DE", "US"],
+      "address": { "country": "DE", "city": "Berlin" },
+      "name": "Erika"
+    }))
+    .unwrap();
+    let disclosures = encoder.conceal_wildcard("/nationalities/*").unwrap();
+    assert_eq!(disclosures.len(), 2);
+    assert!(disclosures.iter().all(|disclosure| disclosure.claim_name.is_none()));
+    assert!(matches!(
+      encoder.conceal_wildcard("/nationalities/*").unwrap_err(),
+      Error::AlreadyConcealed { path } if path == "/nationalities/0"
+    ));
+
+    let disclosures = encoder.conceal_wildcard("/address/*").unwrap();
+    let mut names: Vec<&str> = disclosures
+      .iter()
+      .filter_map(|disclosure| disclosure.claim_name.as_deref())
+      .collect();
+    names.sort_unstable();
+    assert_eq!(names, vec!["city", "country"]);
+    assert_eq!(encoder.object().unwrap()["address"].as_object().unwrap().len(), 1);
+
+    assert!(matches!(
+      encoder.conceal_wildcard("/name/*").unwrap_err(),
+      Error::InvalidPath { .. }
+    ));
+    assert!(encoder.conceal_wildcard("/address").is_err());
+  }
+
+  #[test]
+  fn conceal_wildcard_invalid_sd() {
+    let object = json!({ "a": { "_sd": "x", "b": 1, "c": 2 } });
+    let mut encoder = SdObjectEncoder::try_from(object.clone()).unwrap();
+    assert!(matches!(
+      encoder.conceal_wildcard("/a/*").unwrap_err(),
+      Error::DataTypeMismatch { path, .. } if path == "/a/_sd"
+    ));
+    assert!(encoder.conceal("/a/b", None).is_err());
+    assert_eq!(encoder.object, object);
+    assert_eq!(encoder.disclosures().count(), 0);
+  }
+
+  #[test]
+  fn conceal_wildcard_decoys() {
+    let mut encoder = SdObjectEncoder::try_from(json!({ "nationalities": ["DE", "US"] })).unwrap();
+    encoder.add_decoys("/nationalities", 2).unwrap();
+    let disclosures = encoder.conceal_wildcard("/nationalities/*").unwrap();
+    let values: Vec<&Value> = disclosures.iter().map(|disclosure| &disclosure.claim_value).collect();
+    assert_eq!(values, vec!["DE", "US"]);
+
+    let disclosures: Vec<String> = disclosures.into_iter().map(Disclosure::into_string).collect();
+    let decoded = SdObjectDecoder::new_with_sha256()
+      .decode(encoder.object().unwrap(), &disclosures)
+      .unwrap();
+    assert_eq!(decoded["nationalities"], json!(["DE", "US"]));
+    let decoys = &encoder.trace().decoys["/nationalities"];
+    let nationalities = encoder.object().unwrap()["nationalities"].as_array().unwrap();
+    assert_eq!(nationalities[2][ARRAY_DIGEST_KEY], decoys[0]);
+    assert_eq!(nationalities[3][ARRAY_DIGEST_KEY], decoys[1]);
+  }
+
+  #[test]
+  fn add_decoys_map() {
+    let mut encoder = SdObjectEncoder::try_from(object()).unwrap();
+    let errors = encoder
+      .add_decoys_map([("", 2), ("/id", 1), ("/claim1", 1), ("/missing", 3)])
+      .unwrap_err();
+    let paths: Vec<Option<&str>> = errors.iter().map(Error::path).collect();
+    assert_eq!(paths, vec![Some("/id"), Some("/missing")]);
+    assert!(encoder.trace().decoys.is_empty());
+
+    encoder
+      .add_decoys_map([("", 2), ("/claim1", 1), ("/claim2", 3)])
+      .unwrap();
+    let decoys = encoder.trace().decoys;
+    assert_eq!(decoys[""].len(), 2);
+    assert_eq!(decoys["/claim1"].len(), 1);
+    assert_eq!(encoder.object["claim2"].as_array().unwrap().len(), 5);
+  }
+
+  #[test]
+  fn add_decoys_map_failure() {
+    let mut encoder = SdObjectEncoder::try_from(json!({
+      "list": [1],
+      "invalid": { "_sd": "digest" },
+      "address": { "country": "DE", "city": "Berlin" }
+    }))
+    .unwrap();
+    encoder.conceal("/address/country", None).unwrap();
+    let object = encoder.object().unwrap().clone();
+
+    // The decoys of `""` and `/list` are removed again after adding them to `/invalid` fails.
+    let errors = encoder
+      .add_decoys_map([("", 1), ("/list", 2), ("/invalid", 1)])
+      .unwrap_err();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].path(), Some("/invalid/_sd"));
+    assert_eq!(encoder.object().unwrap(), &object);
+    assert!(encoder.trace().decoys.is_empty());
+
+    // Decoys inside concealed values are removed again, recreating the same disclosures.
+    encoder.conceal("/address", None).unwrap();
+    let sorted = |mut values: Vec<String>| {
+      values.sort();
+      values
+    };
+    let digests = |encoder: &SdObjectEncoder| {
+      sorted(
+        encoder.object().unwrap()["_sd"]
+          .as_array()
+          .unwrap()
+          .iter()
+          .map(|digest| digest.as_str().unwrap().to_owned())
+          .collect(),
+      )
+    };
+    let disclosures = |encoder: &SdObjectEncoder| sorted(encoder.disclosures().map(ToString::to_string).collect());
+    let (expected_digests, expected_disclosures) = (digests(&encoder), disclosures(&encoder));
+    encoder.add_decoys_map([("/address", 2), ("/invalid", 1)]).unwrap_err();
+    assert_eq!(digests(&encoder), expected_digests);
+    assert_eq!(disclosures(&encoder), expected_disclosures);
+    assert!(encoder.trace().decoys.is_empty());
+  }
+
+  #[test]
+  fn nested_decoys() {
+    let hasher = Sha256Hasher::new();
+    let mut encoder = SdObjectEncoder::try_from(json!({ "address": { "country": "DE", "city": "Berlin" } })).unwrap();
+    encoder.conceal("/address/country", None).unwrap();
+    let address = encoder.conceal("/address", None).unwrap();
+    encoder.add_decoys_map([("/address", 2)]).unwrap();
+    assert!(matches!(
+      encoder.add_decoys_map([("/address/city", 1)]).unwrap_err()[0],
+      Error::InvalidPath { .. }
+    ));
+
+    let disclosures: Vec<String> = encoder.disclosures().map(ToString::to_string).collect();
+    assert_eq!(disclosures.len(), 2);
+    assert!(!disclosures.contains(&address.to_string()));
+    let nested = encoder
+      .disclosures()
+      .find(|disclosure| disclosure.claim_name.as_deref() == Some("address"))
+      .unwrap();
+    assert_eq!(nested.salt, address.salt);
+    assert_eq!(nested.claim_value["_sd"].as_array().unwrap().len(), 3);
+    assert_eq!(nested.claim_value["city"], "Berlin");
+    assert_eq!(encoder.trace().decoys["/address"].len(), 2);
+
+    let decoded = SdObjectDecoder::new_with_sha256()
+      .decode(encoder.object().unwrap(), &disclosures)
+      .unwrap();
+    assert_eq!(decoded["address"], json!({ "country": "DE", "city": "Berlin" }));
+    assert_eq!(
+      encoder.object().unwrap()["_sd"][0],
+      hasher.encoded_digest(nested.as_str())
+    );
+  }
+
+  #[test]
+  fn nested_decoys_failure() {
+    let mut encoder =
+      SdObjectEncoder::try_from(json!({ "id": "did:value", "address": { "country": "DE", "city": "Berlin" } }))
+        .unwrap();
+    encoder.conceal("/address/country", None).unwrap();
+    encoder.conceal("/id", None).unwrap();
+    encoder.conceal("/address", None).unwrap();
+    let object = encoder.object().unwrap().clone();
+    let disclosures: Vec<String> = encoder.disclosures().map(ToString::to_string).collect();
+    let assert_unchanged = |encoder: &SdObjectEncoder| {
+      assert_eq!(encoder.object().unwrap(), &object);
+      assert_eq!(
+        encoder.disclosures().map(ToString::to_string).collect::<Vec<_>>(),
+        disclosures
+      );
+      assert!(encoder.trace().decoys.is_empty());
+    };
+
+    // Adding the decoys fails after `/address` and `/address/country` were revealed.
+    assert!(matches!(
+      encoder.add_decoys("/address/country", 1).unwrap_err(),
+      Error::InvalidPath { .. }
+    ));
+    assert_unchanged(&encoder);
+
+    // Adding the decoys to the existing `_sd` of the revealed `/address` fails.
+    let mut encoder = SdObjectEncoder::try_from(json!({ "address": { "_sd": "digest", "city": "Berlin" } })).unwrap();
+    encoder.conceal("/address", None).unwrap();
+    let object = encoder.object().unwrap().clone();
+    let disclosures: Vec<String> = encoder.disclosures().map(ToString::to_string).collect();
+    assert!(matches!(
+      encoder.add_decoys("/address", 1).unwrap_err(),
+      Error::DataTypeMismatch { .. }
     ));
+    assert_eq!(encoder.object().unwrap(), &object);
+    assert_eq!(
+      encoder.disclosures().map(ToString::to_string).collect::<Vec<_>>(),
+      disclosures
+    );
+    assert!(encoder.trace().decoys.is_empty());
+  }
+
+  #[test]
+  fn reveal() {
+    let mut encoder = SdObjectEncoder::try_from(object()).unwrap();
+    encoder.conceal("/claim1/abc", None).unwrap();
+    encoder.conceal("/claim1", None).unwrap();
+    encoder.conceal("/id", None).unwrap();
+    encoder.conceal("/claim2/1", None).unwrap();
+    encoder.add_decoys("", 2).unwrap();
+
+    // `/claim1/abc` is part of the disclosure of `/claim1`.
+    assert!(matches!(
+      encoder.reveal("/claim1/abc").unwrap_err(),
+      Error::InvalidPath { .. }
+    ));
+    let disclosure = encoder.reveal("/id").unwrap();
+    assert_eq!(disclosure.claim_name.as_deref(), Some("id"));
+    encoder.reveal("/claim2/1").unwrap();
+    encoder.reveal("/claim1").unwrap();
+    encoder.reveal("/claim1/abc").unwrap();
+    assert!(matches!(encoder.reveal("/id").unwrap_err(), Error::InvalidPath { .. }));
+
+    let mut expected = object();
+    expected
+      .as_object_mut()
+      .unwrap()
+      .insert("_sd".to_string(), encoder.object.get("_sd").unwrap().clone());
+    assert_eq!(encoder.object, expected);
+    assert_eq!(encoder.object.get("_sd").unwrap().as_array().unwrap().len(), 2);
+    // A revealed path can be concealed again.
+    encoder.conceal("/id", None).unwrap();
+  }
+
+  #[test]
+  fn canonical() {
+    let encode = |object: &str| {
+      let mut encoder = SdObjectEncoder::new(object).unwrap();
+      encoder.set_canonical(true);
+      let claim = encoder
+        .conceal("/claim", Some("2GLC42sKQveCfGfryNRN9w".to_owned()))
+        .unwrap();
+      let element = encoder
+        .conceal("/list/0", Some("2GLC42sKQveCfGfryNRN9w".to_owned()))
+        .unwrap();
+      (claim, element, encoder.try_to_string().unwrap())
+    };
+    // The claim values differ in their number representation, the serialization does not.
+    let (claim, element, object) = encode(r#"{"list": [1.50], "claim": {"b": 1e2, "a": "x"}, "id": "did:value"}"#);
+    let (other_claim, other_element, other_object) =
+      encode(r#"{"id":"did:value","claim":{"a":"x","b":100},"list":[1.5]}"#);
+    assert_eq!(claim.as_str(), other_claim.as_str());
+    assert_eq!(element.as_str(), other_element.as_str());
+    assert_eq!(object, other_object);
+
+    let decoded = |disclosure: &crate::Disclosure| {
+      String::from_utf8(multibase::Base::Base64Url.decode(disclosure.as_str()).unwrap()).unwrap()
+    };
+    assert_eq!(
+      decoded(&claim),
+      r#"["2GLC42sKQveCfGfryNRN9w","claim",{"a":"x","b":100}]"#
+    );
+    assert_eq!(decoded(&element), r#"["2GLC42sKQveCfGfryNRN9w",1.5]"#);
+    let hasher = Sha256Hasher::new();
+    assert_eq!(
+      object,
+      format!(
+        r#"{{"_sd":["{}"],"id":"did:value","list":[{{"...":"{}"}}]}}"#,
+        hasher.encoded_digest(claim.as_str()),
+        hasher.encoded_digest(element.as_str())
+      )
+    );
   }
 
   #[test]
@@ -361,11 +1258,31 @@ mod test {
     let mut encoder = SdObjectEncoder::try_from(object()).unwrap();
     assert!(matches!(
       encoder.conceal("/claim12", None).unwrap_err(),
-      Error::InvalidPath(_)
+      Error::InvalidPath { .. }
     ));
     assert!(matches!(
       encoder.conceal("/claim12/0", None).unwrap_err(),
-      Error::InvalidPath(_)
+      Error::InvalidPath { .. }
+    ));
+    assert_eq!(
+      encoder.conceal("/claim2/5", None).unwrap_err().path(),
+      Some("/claim2/5")
+    );
+  }
+
+  #[test]
+  fn custom_hasher() {
+    let mut encoder = SdObjectEncoder::with_custom_hasher(&object().to_string(), Sha256Hasher::new()).unwrap();
+    encoder.conceal("/id", None).unwrap();
+    encoder.conceal("/claim2/0", None).unwrap();
+    let object = encoder.into_object();
+    assert!(object.get("id").is_none());
+    assert_eq!(object.get("_sd").unwrap().as_array().unwrap().len(), 1);
+    assert!(matches!(
+      SdObjectEncoder::from_value_with_hasher(json!(["claim"]), Sha256Hasher::new())
+        .err()
+        .unwrap(),
+      Error::DataTypeMismatch { .. }
     ));
   }
 
@@ -383,4 +1300,36 @@ mod test {
     assert_eq!(encoder.object.get("_sd").unwrap().as_array().unwrap().len(), 11);
     assert_eq!(encoder.object.get("claim2").unwrap().as_array().unwrap().len(), 12);
   }
+
+  #[test]
+  fn trace() {
+    let mut encoder = SdObjectEncoder::try_from(object()).unwrap();
+    let disclosure = encoder
+      .conceal("/id", Some("2GLC42sKQveCfGfryNRN9w".to_owned()))
+      .unwrap();
+    encoder.conceal("/claim2/0", None).unwrap();
+    encoder.reveal("/claim2/0").unwrap();
+    encoder.add_decoys("", 2).unwrap();
+    encoder.add_decoys("/claim2", 1).unwrap();
+
+    let trace = encoder.trace();
+    assert_eq!(trace.sd_alg, "sha-256");
+    assert_eq!(trace.disclosures.len(), 1);
+    assert_eq!(trace.disclosures[0].path, "/id");
+    assert_eq!(trace.disclosures[0].salt, "2GLC42sKQveCfGfryNRN9w");
+    assert_eq!(
+      trace.disclosures[0].digest,
+      Sha256Hasher::new().encoded_digest(disclosure.as_str())
+    );
+
+    let digests = encoder.object.get("_sd").unwrap().as_array().unwrap();
+    assert_eq!(trace.decoys[""].len(), 2);
+    assert!(trace.decoys[""]
+      .iter()
+      .all(|decoy| digests.contains(&Value::String(decoy.clone()))));
+    assert_eq!(
+      encoder.object["claim2"][2]["..."],
+      Value::String(trace.decoys["/claim2"][0].clone())
+    );
+  }
 }
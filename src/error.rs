@@ -4,6 +4,10 @@
 /// Alias for a `Result` with the error type [`Error`].
 pub type Result<T> = ::core::result::Result<T, Error>;
 
+/// Errors that can occur while encoding, decoding or parsing SD-JWTs.
+///
+/// Paths are [JSON pointers](https://datatracker.ietf.org/doc/html/rfc6901) into the object being
+/// encoded or decoded, the empty string denotes the top level.
 #[derive(Debug, thiserror::Error, strum::IntoStaticStr)]
 #[non_exhaustive]
 pub enum Error {
@@ -13,20 +17,20 @@ pub enum Error {
   #[error("no hasher can be specified for the hashing algorithm {0}")]
   MissingHasher(String),
 
-  #[error("data type is not expected: {0}")]
-  DataTypeMismatch(String),
+  #[error("data type is not expected at `{path}`: {reason}")]
+  DataTypeMismatch { path: String, reason: String },
 
-  #[error("claim {0} of disclosure already exists")]
-  ClaimCollisionError(String),
+  #[error("claim {claim_name} of disclosure already exists at `{path}`")]
+  ClaimCollisionError { path: String, claim_name: String },
 
-  #[error("digest {0} appears multiple times")]
-  DuplicateDigestError(String),
+  #[error("digest {digest} appears multiple times")]
+  DuplicateDigestError { path: String, digest: String },
 
-  #[error("array disclosure object contains keys other than `...`")]
-  InvalidArrayDisclosureObject,
+  #[error("array disclosure object at `{path}` contains keys other than `...`")]
+  InvalidArrayDisclosureObject { path: String },
 
-  #[error("invalid path: {0}")]
-  InvalidPath(String),
+  #[error("invalid path `{path}`: {reason}")]
+  InvalidPath { path: String, reason: String },
 
   #[error("invalid input")]
   DeserializationError(String),
@@ -34,9 +38,129 @@ pub enum Error {
   #[error("{0}")]
   Unspecified(String),
 
-  #[error("salt size must be greater than or equal to 16")]
-  InvalidSaltSize,
-
   #[error("the validation ended with {0} unused disclosure(s)")]
   UnusedDisclosures(usize),
+
+  #[error("nonce {0} was not issued, has expired or was already used")]
+  InvalidNonce(String),
+
+  #[error("the value at `{path}` is already concealed")]
+  AlreadyConcealed { path: String },
+
+  #[error("more than {max} digests without matching disclosure, exceeded at `{path}`")]
+  UnmatchedDigestLimitExceeded { path: String, max: usize },
+
+  #[error("the reconstructed payload differs from the issuer-signed JWT at `{path}`")]
+  ReconstructionMismatch { path: String },
+
+  #[error("expected JWT typ {expected}, found {found:?}")]
+  UnexpectedTyp { expected: String, found: Option<String> },
+
+  #[error("expected JWT alg {expected}, found {found:?}")]
+  UnexpectedAlg { expected: String, found: Option<String> },
+
+  #[error("{0} is not a hash name of the IANA \"Named Information Hash Algorithm\" registry")]
+  UnregisteredHashAlgorithm(String),
+
+  #[error("invalid DID: {0}")]
+  InvalidDid(String),
+
+  #[error("salt of {size} bytes is shorter than the minimum of {min_size} bytes")]
+  SaltTooShort { size: usize, min_size: usize },
+}
+
+/// Machine-readable category of an [`Error`].
+///
+/// Unlike error messages, the kinds and their string representations are stable and can be matched on
+/// or forwarded to other systems.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, strum::IntoStaticStr, strum::Display)]
+#[strum(serialize_all = "snake_case")]
+#[non_exhaustive]
+pub enum ErrorKind {
+  InvalidDisclosure,
+  MissingHasher,
+  DataTypeMismatch,
+  ClaimCollision,
+  DuplicateDigest,
+  InvalidArrayDisclosureObject,
+  InvalidPath,
+  Deserialization,
+  Unspecified,
+  UnusedDisclosures,
+  InvalidNonce,
+  AlreadyConcealed,
+  UnmatchedDigestLimitExceeded,
+  ReconstructionMismatch,
+  UnexpectedTyp,
+  UnexpectedAlg,
+  UnregisteredHashAlgorithm,
+  InvalidDid,
+  SaltTooShort,
+}
+
+impl ErrorKind {
+  /// Returns the stable string representation of this kind, e.g. `"claim_collision"`.
+  pub fn as_str(&self) -> &'static str {
+    self.into()
+  }
+}
+
+impl Error {
+  /// Returns the [`ErrorKind`] of this error.
+  pub fn kind(&self) -> ErrorKind {
+    match self {
+      Self::InvalidDisclosure(_) => ErrorKind::InvalidDisclosure,
+      Self::MissingHasher(_) => ErrorKind::MissingHasher,
+      Self::DataTypeMismatch { .. } => ErrorKind::DataTypeMismatch,
+      Self::ClaimCollisionError { .. } => ErrorKind::ClaimCollision,
+      Self::DuplicateDigestError { .. } => ErrorKind::DuplicateDigest,
+      Self::InvalidArrayDisclosureObject { .. } => ErrorKind::InvalidArrayDisclosureObject,
+      Self::InvalidPath { .. } => ErrorKind::InvalidPath,
+      Self::DeserializationError(_) => ErrorKind::Deserialization,
+      Self::Unspecified(_) => ErrorKind::Unspecified,
+      Self::UnusedDisclosures(_) => ErrorKind::UnusedDisclosures,
+      Self::InvalidNonce(_) => ErrorKind::InvalidNonce,
+      Self::AlreadyConcealed { .. } => ErrorKind::AlreadyConcealed,
+      Self::UnmatchedDigestLimitExceeded { .. } => ErrorKind::UnmatchedDigestLimitExceeded,
+      Self::ReconstructionMismatch { .. } => ErrorKind::ReconstructionMismatch,
+      Self::UnexpectedTyp { .. } => ErrorKind::UnexpectedTyp,
+      Self::UnexpectedAlg { .. } => ErrorKind::UnexpectedAlg,
+      Self::UnregisteredHashAlgorithm(_) => ErrorKind::UnregisteredHashAlgorithm,
+      Self::InvalidDid(_) => ErrorKind::InvalidDid,
+      Self::SaltTooShort { .. } => ErrorKind::SaltTooShort,
+    }
+  }
+
+  /// Returns the JSON pointer to the location the error occurred at, if known.
+  pub fn path(&self) -> Option<&str> {
+    match self {
+      Self::DataTypeMismatch { path, .. }
+      | Self::ClaimCollisionError { path, .. }
+      | Self::DuplicateDigestError { path, .. }
+      | Self::InvalidArrayDisclosureObject { path }
+      | Self::InvalidPath { path, .. }
+      | Self::AlreadyConcealed { path }
+      | Self::UnmatchedDigestLimitExceeded { path, .. }
+      | Self::ReconstructionMismatch { path } => Some(path),
+      _ => None,
+    }
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::Error;
+  use super::ErrorKind;
+
+  #[test]
+  fn kind_and_path() {
+    let error = Error::ClaimCollisionError {
+      path: "/address".to_string(),
+      claim_name: "country".to_string(),
+    };
+    assert_eq!(error.kind(), ErrorKind::ClaimCollision);
+    assert_eq!(error.kind().as_str(), "claim_collision");
+    assert_eq!(error.path(), Some("/address"));
+    assert_eq!(Error::SaltTooShort { size: 8, min_size: 16 }.path(), None);
+  }
 }
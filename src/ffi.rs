@@ -0,0 +1,463 @@
+// Copyright 2020-2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! C-compatible interface to encode, parse, decode and validate SD-JWTs.
+//!
+//! All functions are `cbindgen` compatible. [`SdJwt`] and [`SdObjectEncoder`] are exposed as opaque
+//! handles which must be released with [`sd_jwt_free`] and [`sd_jwt_encoder_free`] respectively. Every
+//! string returned by this module is owned by the caller and must be released with [`sd_jwt_string_free`].
+//!
+//! Functions return a null pointer or a negative value on failure, the reason can be retrieved with
+//! [`sd_jwt_last_error`] on the same thread.
+//!
+//! Like the Rust API, this module does not verify signatures. [`sd_jwt_validate`] runs all other checks of
+//! [`SdJwt::validate_full`], the JWTs must be verified by the caller before trusting the decoded claims.
+//!
+//! Building a shared or static library requires setting the crate type explicitly, e.g.
+//! `cargo rustc --release --features ffi --crate-type cdylib`.
+
+use std::cell::RefCell;
+use std::ffi::CStr;
+use std::ffi::CString;
+use std::os::raw::c_char;
+use std::ptr;
+
+use serde_json::Map;
+use serde_json::Value;
+
+use crate::Error;
+use crate::HasherRegistry;
+use crate::SdJwt;
+use crate::SdObjectDecoder;
+use crate::SdObjectEncoder;
+use crate::ValidationOptions;
+
+thread_local! {
+  static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(error: impl ToString) {
+  let message = CString::new(error.to_string().replace('\0', "")).unwrap_or_default();
+  LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+}
+
+/// Reads a caller-provided C string.
+///
+/// ## Safety
+/// `input` must be null or a valid pointer to a nul-terminated string.
+unsafe fn read_str<'a>(input: *const c_char) -> Result<&'a str, Error> {
+  if input.is_null() {
+    return Err(Error::Unspecified("unexpected null pointer".to_string()));
+  }
+  CStr::from_ptr(input)
+    .to_str()
+    .map_err(|_| Error::DeserializationError("input is not valid UTF-8".to_string()))
+}
+
+/// Reads a caller-provided C string that may be null.
+///
+/// ## Safety
+/// `input` must be null or a valid pointer to a nul-terminated string.
+unsafe fn read_optional_str<'a>(input: *const c_char) -> Result<Option<&'a str>, Error> {
+  if input.is_null() {
+    Ok(None)
+  } else {
+    read_str(input).map(Some)
+  }
+}
+
+fn into_c_string(output: Result<String, Error>) -> *mut c_char {
+  match output.and_then(|output| CString::new(output).map_err(|e| Error::Unspecified(e.to_string()))) {
+    Ok(output) => output.into_raw(),
+    Err(error) => {
+      set_last_error(error);
+      ptr::null_mut()
+    }
+  }
+}
+
+/// Returns the message of the last error that occurred on the calling thread, or null if none occurred.
+///
+/// The returned string must be released with [`sd_jwt_string_free`].
+#[no_mangle]
+pub extern "C" fn sd_jwt_last_error() -> *mut c_char {
+  LAST_ERROR.with(|last| last.borrow_mut().take().map_or(ptr::null_mut(), CString::into_raw))
+}
+
+/// Releases a string returned by this library.
+///
+/// ## Safety
+/// `string` must be null or a pointer returned by this library that was not released before.
+#[no_mangle]
+pub unsafe extern "C" fn sd_jwt_string_free(string: *mut c_char) {
+  if !string.is_null() {
+    drop(CString::from_raw(string));
+  }
+}
+
+/// Parses an SD-JWT and returns a handle to it, or null on failure.
+///
+/// ## Safety
+/// `sd_jwt` must be a valid pointer to a nul-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn sd_jwt_parse(sd_jwt: *const c_char) -> *mut SdJwt {
+  match read_str(sd_jwt).and_then(SdJwt::parse) {
+    Ok(sd_jwt) => Box::into_raw(Box::new(sd_jwt)),
+    Err(error) => {
+      set_last_error(error);
+      ptr::null_mut()
+    }
+  }
+}
+
+/// Creates an SD-JWT from a signed `jwt` and the `disclosures` concealed in its claims.
+///
+/// ## Safety
+/// `jwt` must be a valid pointer to a nul-terminated string, `disclosures` must point to
+/// `disclosures_len` valid pointers to nul-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn sd_jwt_new(
+  jwt: *const c_char,
+  disclosures: *const *const c_char,
+  disclosures_len: usize,
+) -> *mut SdJwt {
+  let result = (|| {
+    let jwt = read_str(jwt)?.to_owned();
+    let disclosures = if disclosures_len == 0 {
+      vec![]
+    } else if disclosures.is_null() {
+      return Err(Error::Unspecified("unexpected null pointer".to_string()));
+    } else {
+      std::slice::from_raw_parts(disclosures, disclosures_len)
+        .iter()
+        .map(|disclosure| read_str(*disclosure).map(ToOwned::to_owned))
+        .collect::<Result<Vec<String>, Error>>()?
+    };
+    Ok(SdJwt::new(jwt, disclosures, None))
+  })();
+
+  match result {
+    Ok(sd_jwt) => Box::into_raw(Box::new(sd_jwt)),
+    Err(error) => {
+      set_last_error(error);
+      ptr::null_mut()
+    }
+  }
+}
+
+/// Serializes an SD-JWT into its presentation format.
+///
+/// ## Safety
+/// `sd_jwt` must be a valid handle returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn sd_jwt_presentation(sd_jwt: *const SdJwt) -> *mut c_char {
+  match sd_jwt.as_ref() {
+    Some(sd_jwt) => into_c_string(Ok(sd_jwt.presentation())),
+    None => into_c_string(Err(Error::Unspecified("unexpected null pointer".to_string()))),
+  }
+}
+
+/// Returns the number of disclosures of an SD-JWT, or `-1` on failure.
+///
+/// ## Safety
+/// `sd_jwt` must be a valid handle returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn sd_jwt_disclosures_len(sd_jwt: *const SdJwt) -> isize {
+  match sd_jwt.as_ref() {
+    Some(sd_jwt) => sd_jwt.disclosures.len() as isize,
+    None => {
+      set_last_error("unexpected null pointer");
+      -1
+    }
+  }
+}
+
+/// Removes the disclosure at `index` from the SD-JWT, concealing its value from the verifier.
+///
+/// Returns the removed disclosure, or null on failure.
+///
+/// ## Safety
+/// `sd_jwt` must be a valid handle returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn sd_jwt_remove_disclosure(sd_jwt: *mut SdJwt, index: usize) -> *mut c_char {
+  match sd_jwt.as_mut() {
+    Some(sd_jwt) if index < sd_jwt.disclosures.len() => into_c_string(Ok(sd_jwt.disclosures.remove(index))),
+    Some(_) => into_c_string(Err(Error::Unspecified(format!("no disclosure at index {}", index)))),
+    None => into_c_string(Err(Error::Unspecified("unexpected null pointer".to_string()))),
+  }
+}
+
+/// Decodes the `claims` of the SD-JWT's JWT, given as a JSON object, using the SD-JWT's disclosures.
+///
+/// The hasher is determined by the `_sd_alg` claim, see [`SdObjectDecoder::default`] for the supported
+/// algorithms. Verifying the JWT's signature and extracting its claims is left to the caller. Returns the
+/// decoded object as JSON, or null on failure.
+///
+/// ## Safety
+/// `sd_jwt` must be a valid handle returned by this library and `claims` a valid pointer to a
+/// nul-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn sd_jwt_decode(sd_jwt: *const SdJwt, claims: *const c_char) -> *mut c_char {
+  let result = (|| {
+    let sd_jwt = sd_jwt
+      .as_ref()
+      .ok_or(Error::Unspecified("unexpected null pointer".to_string()))?;
+    let claims: Map<String, Value> =
+      serde_json::from_str(read_str(claims)?).map_err(|e| Error::DeserializationError(e.to_string()))?;
+    let decoded = SdObjectDecoder::default().decode(&claims, &sd_jwt.disclosures)?;
+    serde_json::to_string(&decoded).map_err(|e| Error::Unspecified(e.to_string()))
+  })();
+  into_c_string(result)
+}
+
+/// Validates the SD-JWT like [`SdJwt::validate_full`] at `now`, in seconds since the Unix epoch, and returns the
+/// [`ValidationReport`](crate::ValidationReport) as JSON, or null on failure.
+///
+/// The hasher is determined by the `_sd_alg` claim of the JWT. The key binding JWT must have the `nonce` and
+/// `aud` given, they are not checked if null. Signatures are not verified.
+///
+/// ## Safety
+/// `sd_jwt` must be a valid handle returned by this library, `nonce` and `audience` must be null or valid
+/// pointers to nul-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn sd_jwt_validate(
+  sd_jwt: *const SdJwt,
+  now: i64,
+  nonce: *const c_char,
+  audience: *const c_char,
+) -> *mut c_char {
+  let result = (|| {
+    let sd_jwt = sd_jwt
+      .as_ref()
+      .ok_or(Error::Unspecified("unexpected null pointer".to_string()))?;
+    let options = ValidationOptions {
+      now,
+      nonce: read_optional_str(nonce)?.map(ToOwned::to_owned),
+      audience: read_optional_str(audience)?.map(ToOwned::to_owned),
+      ..Default::default()
+    };
+    let registry = HasherRegistry::with_available_hashers();
+    let hasher = registry.determine_hasher(&sd_jwt.parse_jwt()?.into_claims())?;
+    let report = sd_jwt.validate_full(hasher, &options);
+    serde_json::to_string(&report).map_err(|e| Error::Unspecified(e.to_string()))
+  })();
+  into_c_string(result)
+}
+
+/// Releases an SD-JWT handle.
+///
+/// ## Safety
+/// `sd_jwt` must be null or a handle returned by this library that was not released before.
+#[no_mangle]
+pub unsafe extern "C" fn sd_jwt_free(sd_jwt: *mut SdJwt) {
+  if !sd_jwt.is_null() {
+    drop(Box::from_raw(sd_jwt));
+  }
+}
+
+/// Creates an encoder with the `sha-256` hash function for the JSON `object`, or null on failure.
+///
+/// ## Safety
+/// `object` must be a valid pointer to a nul-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn sd_jwt_encoder_new(object: *const c_char) -> *mut SdObjectEncoder {
+  match read_str(object).and_then(SdObjectEncoder::new) {
+    Ok(encoder) => Box::into_raw(Box::new(encoder)),
+    Err(error) => {
+      set_last_error(error);
+      ptr::null_mut()
+    }
+  }
+}
+
+/// Conceals the value at the JSON pointer `path` with a random salt, see [`SdObjectEncoder::conceal`].
+///
+/// Returns the created disclosure, or null on failure.
+///
+/// ## Safety
+/// `encoder` must be a valid handle returned by this library and `path` a valid pointer to a
+/// nul-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn sd_jwt_encoder_conceal(encoder: *mut SdObjectEncoder, path: *const c_char) -> *mut c_char {
+  let result = (|| {
+    let encoder = encoder
+      .as_mut()
+      .ok_or(Error::Unspecified("unexpected null pointer".to_string()))?;
+    encoder
+      .conceal(read_str(path)?, None)
+      .map(|disclosure| disclosure.to_string())
+  })();
+  into_c_string(result)
+}
+
+/// Adds `number_of_decoys` decoy digests to the JSON pointer `path`, see [`SdObjectEncoder::add_decoys`].
+///
+/// Returns `0` on success, or `-1` on failure.
+///
+/// ## Safety
+/// `encoder` must be a valid handle returned by this library and `path` a valid pointer to a
+/// nul-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn sd_jwt_encoder_add_decoys(
+  encoder: *mut SdObjectEncoder,
+  path: *const c_char,
+  number_of_decoys: usize,
+) -> i32 {
+  let result = (|| {
+    let encoder = encoder
+      .as_mut()
+      .ok_or(Error::Unspecified("unexpected null pointer".to_string()))?;
+    encoder.add_decoys(read_str(path)?, number_of_decoys)
+  })();
+  match result {
+    Ok(()) => 0,
+    Err(error) => {
+      set_last_error(error);
+      -1
+    }
+  }
+}
+
+/// Adds the `_sd_alg` property to the encoded object and returns it as JSON, or null on failure.
+///
+/// The returned object is meant to be used as the claims of the JWT signed by the caller.
+///
+/// ## Safety
+/// `encoder` must be a valid handle returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn sd_jwt_encoder_finish(encoder: *mut SdObjectEncoder) -> *mut c_char {
+  let result = (|| {
+    let encoder = encoder
+      .as_mut()
+      .ok_or(Error::Unspecified("unexpected null pointer".to_string()))?;
+    encoder.add_sd_alg_property();
+    encoder.try_to_string()
+  })();
+  into_c_string(result)
+}
+
+/// Releases an encoder handle.
+///
+/// ## Safety
+/// `encoder` must be null or a handle returned by this library that was not released before.
+#[no_mangle]
+pub unsafe extern "C" fn sd_jwt_encoder_free(encoder: *mut SdObjectEncoder) {
+  if !encoder.is_null() {
+    drop(Box::from_raw(encoder));
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::test_utils;
+  use serde_json::json;
+
+  fn c_string(input: &str) -> CString {
+    CString::new(input).unwrap()
+  }
+
+  unsafe fn take_string(output: *mut c_char) -> String {
+    assert!(!output.is_null());
+    let string = CStr::from_ptr(output).to_str().unwrap().to_owned();
+    sd_jwt_string_free(output);
+    string
+  }
+
+  #[test]
+  fn encode_present_decode() {
+    unsafe {
+      let encoder = sd_jwt_encoder_new(c_string(r#"{"name":"John","age":42}"#).as_ptr());
+      let disclosure = take_string(sd_jwt_encoder_conceal(encoder, c_string("/name").as_ptr()));
+      assert_eq!(sd_jwt_encoder_add_decoys(encoder, c_string("").as_ptr(), 2), 0);
+      let claims = take_string(sd_jwt_encoder_finish(encoder));
+      sd_jwt_encoder_free(encoder);
+
+      // The JWT is not verified by the decoder, only its presentation matters here.
+      let disclosure = c_string(&disclosure);
+      let disclosures = [disclosure.as_ptr()];
+      let sd_jwt = sd_jwt_new(
+        c_string(&test_utils::jwt(json!({ "alg": "ES256" }), "{}")).as_ptr(),
+        disclosures.as_ptr(),
+        1,
+      );
+      let presentation = take_string(sd_jwt_presentation(sd_jwt));
+      sd_jwt_free(sd_jwt);
+
+      let sd_jwt = sd_jwt_parse(c_string(&presentation).as_ptr());
+      assert_eq!(sd_jwt_disclosures_len(sd_jwt), 1);
+      let decoded = take_string(sd_jwt_decode(sd_jwt, c_string(&claims).as_ptr()));
+      assert_eq!(
+        serde_json::from_str::<Value>(&decoded).unwrap(),
+        json!({"name": "John", "age": 42})
+      );
+
+      take_string(sd_jwt_remove_disclosure(sd_jwt, 0));
+      let decoded = take_string(sd_jwt_decode(sd_jwt, c_string(&claims).as_ptr()));
+      assert_eq!(serde_json::from_str::<Value>(&decoded).unwrap(), json!({"age": 42}));
+      sd_jwt_free(sd_jwt);
+    }
+  }
+
+  #[cfg(feature = "sha-512")]
+  #[test]
+  fn decode_sha_512() {
+    let mut encoder = SdObjectEncoder::with_custom_hasher(r#"{"name":"John"}"#, crate::Sha512Hasher::new()).unwrap();
+    let disclosure = c_string(encoder.conceal("/name", None).unwrap().as_str());
+    encoder.add_sd_alg_property();
+    let claims = encoder.try_to_string().unwrap();
+    unsafe {
+      let disclosures = [disclosure.as_ptr()];
+      let sd_jwt = sd_jwt_new(
+        c_string(&test_utils::jwt(json!({ "alg": "ES256" }), "{}")).as_ptr(),
+        disclosures.as_ptr(),
+        1,
+      );
+      let decoded = take_string(sd_jwt_decode(sd_jwt, c_string(&claims).as_ptr()));
+      assert_eq!(
+        serde_json::from_str::<Value>(&decoded).unwrap(),
+        json!({"name": "John"})
+      );
+      sd_jwt_free(sd_jwt);
+    }
+  }
+
+  #[test]
+  fn validate() {
+    let mut encoder = SdObjectEncoder::try_from(json!({"name": "John", "exp": 1000})).unwrap();
+    let disclosure = c_string(encoder.conceal("/name", None).unwrap().as_str());
+    encoder.add_sd_alg_property();
+    let jwt = test_utils::jwt(json!({ "alg": "ES256" }), encoder.try_to_string().unwrap());
+    unsafe {
+      let disclosures = [disclosure.as_ptr()];
+      let sd_jwt = sd_jwt_new(c_string(&jwt).as_ptr(), disclosures.as_ptr(), 1);
+      let report = take_string(sd_jwt_validate(sd_jwt, 500, ptr::null(), ptr::null()));
+      let report: crate::ValidationReport = serde_json::from_str(&report).unwrap();
+      assert!(report.is_valid(), "{:?}", report);
+
+      let report = take_string(sd_jwt_validate(sd_jwt, 2000, ptr::null(), ptr::null()));
+      let report: crate::ValidationReport = serde_json::from_str(&report).unwrap();
+      assert!(report
+        .errors()
+        .any(|finding| finding.check == crate::ValidationCheck::Time));
+      sd_jwt_free(sd_jwt);
+
+      assert!(sd_jwt_validate(ptr::null(), 0, ptr::null(), ptr::null()).is_null());
+      assert!(take_string(sd_jwt_last_error()).contains("null pointer"));
+    }
+  }
+
+  #[test]
+  fn errors() {
+    unsafe {
+      let encoder = sd_jwt_encoder_new(c_string("[]").as_ptr());
+      assert!(encoder.is_null());
+      let error = take_string(sd_jwt_last_error());
+      assert!(error.contains("expected object"));
+      assert!(sd_jwt_last_error().is_null());
+
+      assert!(sd_jwt_parse(ptr::null()).is_null());
+      assert!(take_string(sd_jwt_last_error()).contains("null pointer"));
+    }
+  }
+}
@@ -3,11 +3,25 @@
 
 #[cfg(feature = "sha")]
 use crypto::hashes::sha::SHA256;
+use serde_json::Map;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::pin::Pin;
+
+use crate::Error;
+use crate::Result;
 
 #[cfg(feature = "sha")]
 use crypto::hashes::sha::SHA256_LEN;
+#[cfg(feature = "sha-512")]
+use crypto::hashes::sha::SHA512;
+#[cfg(feature = "sha-512")]
+use crypto::hashes::sha::SHA512_LEN;
 
 pub const SHA_ALG_NAME: &str = "sha-256";
+/// Name of the `sha-512` hash algorithm.
+pub const SHA512_ALG_NAME: &str = "sha-512";
 
 /// Used to implement hash functions to be used for encoding/decoding.
 ///
@@ -31,10 +45,32 @@ pub trait Hasher: Sync + Send {
   /// Returns the base64url-encoded digest of a `disclosure`.
   fn encoded_digest(&self, disclosure: &str) -> String {
     let hash = self.digest(disclosure.as_bytes());
-    multibase::Base::Base64Url.encode(hash)
+    crate::base64::encode(hash)
   }
 }
 
+/// Future returned by [`AsyncHasher::digest`].
+pub type DigestFuture<'a> = Pin<Box<dyn Future<Output = Vec<u8>> + Send + 'a>>;
+
+/// Asynchronous counterpart of [`Hasher`], for hash functions computed outside of the process without blocking
+/// the executor, e.g. inside an HSM or a TEE.
+///
+/// See [`SdJwt::sd_hash_async`](crate::SdJwt::sd_hash_async) and
+/// [`KeyBindingJwtClaims::new_async`](crate::KeyBindingJwtClaims::new_async).
+pub trait AsyncHasher: Sync + Send {
+  /// Digests input to produce unique fixed-size hash value in bytes.
+  fn digest<'a>(&'a self, input: &'a [u8]) -> DigestFuture<'a>;
+
+  /// Returns the name of hash function used, see [`Hasher::alg_name`].
+  fn alg_name(&self) -> &'static str;
+}
+
+/// Returns the base64url-encoded digest of `input`, computed by `hasher`.
+pub async fn encoded_digest_async(hasher: &dyn AsyncHasher, input: &str) -> String {
+  let hash = hasher.digest(input.as_bytes()).await;
+  crate::base64::encode(hash)
+}
+
 /// An implementation of [`Hasher`] that uses the `sha-256` hash function.
 #[derive(Default, Clone, Copy)]
 #[cfg(feature = "sha")]
@@ -60,9 +96,185 @@ impl Hasher for Sha256Hasher {
   }
 }
 
+/// An implementation of [`Hasher`] that uses the `sha-512` hash function.
+#[derive(Default, Clone, Copy)]
+#[cfg(feature = "sha-512")]
+pub struct Sha512Hasher;
+
+#[cfg(feature = "sha-512")]
+impl Sha512Hasher {
+  /// Creates a new [`Sha512Hasher`]
+  pub fn new() -> Self {
+    Sha512Hasher {}
+  }
+}
+
+#[cfg(feature = "sha-512")]
+impl Hasher for Sha512Hasher {
+  fn digest(&self, input: &[u8]) -> Vec<u8> {
+    let mut digest: [u8; SHA512_LEN] = [0; SHA512_LEN];
+    SHA512(input, &mut digest);
+    digest.to_vec()
+  }
+
+  fn alg_name(&self) -> &'static str {
+    SHA512_ALG_NAME
+  }
+}
+
+/// Hash names of the IANA ["Named Information Hash Algorithm" registry](https://www.iana.org/assignments/named-information/named-information.xhtml).
+pub const IANA_HASH_ALG_NAMES: &[&str] = &[
+  "sha-256",
+  "sha-256-128",
+  "sha-256-120",
+  "sha-256-96",
+  "sha-256-64",
+  "sha-256-32",
+  "sha-384",
+  "sha-512",
+  "sha3-224",
+  "sha3-256",
+  "sha3-384",
+  "sha3-512",
+  "blake2s-256",
+  "blake2b-256",
+  "blake2b-512",
+  "k12-256",
+  "k12-512",
+];
+
+/// Set of [`Hasher`]s by [`Hasher::alg_name`], used to look up the hasher of an SD-JWT by its `_sd_alg`.
+///
+/// A registry can be built once and used for decoding, see
+/// [`SdObjectDecoder::with_registry`](crate::SdObjectDecoder::with_registry), as well as for verifying disclosures
+/// and key binding JWTs, see [`HasherRegistry::determine_hasher`].
+#[derive(Default)]
+pub struct HasherRegistry {
+  hashers: BTreeMap<String, Box<dyn Hasher>>,
+}
+
+impl HasherRegistry {
+  /// Creates a new [`HasherRegistry`] without any hashers.
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Creates a new [`HasherRegistry`] with the `sha-256` hasher.
+  #[cfg(feature = "sha")]
+  pub fn with_sha256() -> Self {
+    let mut registry = Self::new();
+    registry
+      .hashers
+      .insert(SHA_ALG_NAME.to_owned(), Box::new(Sha256Hasher::new()));
+    registry
+  }
+
+  /// Creates a new [`HasherRegistry`] with all hashers provided by the enabled features, i.e. `sha-256` with
+  /// `sha` and `sha-512` with `sha-512`.
+  pub fn with_available_hashers() -> Self {
+    #[allow(unused_mut)]
+    let mut registry = Self::new();
+    #[cfg(feature = "sha")]
+    registry
+      .hashers
+      .insert(SHA_ALG_NAME.to_owned(), Box::new(Sha256Hasher::new()));
+    #[cfg(feature = "sha-512")]
+    registry
+      .hashers
+      .insert(SHA512_ALG_NAME.to_owned(), Box::new(Sha512Hasher::new()));
+    registry
+  }
+
+  /// Registers `hasher` for its [`Hasher::alg_name`].
+  ///
+  /// If a hasher for the same algorithm is already registered, it will be replaced and returned,
+  /// otherwise `None`.
+  ///
+  /// ## Error
+  /// Returns [`Error::UnregisteredHashAlgorithm`] if the name is not listed in [`IANA_HASH_ALG_NAMES`].
+  pub fn register(&mut self, hasher: Box<dyn Hasher>) -> Result<Option<Box<dyn Hasher>>> {
+    let alg_name = hasher.alg_name();
+    if !IANA_HASH_ALG_NAMES.contains(&alg_name) {
+      return Err(Error::UnregisteredHashAlgorithm(alg_name.to_owned()));
+    }
+    Ok(self.hashers.insert(alg_name.to_owned(), hasher))
+  }
+
+  /// Removes the hasher for `alg_name` and returns it, if registered.
+  pub fn remove(&mut self, alg_name: &str) -> Option<Box<dyn Hasher>> {
+    self.hashers.remove(alg_name)
+  }
+
+  /// Returns the hasher for `alg_name`, if registered.
+  pub fn get(&self, alg_name: &str) -> Option<&dyn Hasher> {
+    self.hashers.get(alg_name).map(AsRef::as_ref)
+  }
+
+  /// Returns the names of the registered algorithms.
+  pub fn alg_names(&self) -> impl Iterator<Item = &str> {
+    self.hashers.keys().map(String::as_str)
+  }
+
+  /// Returns the hasher for the `_sd_alg` property of `object`, `sha-256` if the property is not set.
+  ///
+  /// ## Error
+  /// * [`Error::DataTypeMismatch`] if `_sd_alg` is not a string.
+  /// * [`Error::MissingHasher`] if no hasher is registered for the algorithm.
+  pub fn determine_hasher(&self, object: &Map<String, Value>) -> Result<&dyn Hasher> {
+    let alg = crate::decoder::sd_alg(object)?;
+    self.get(alg).ok_or_else(|| Error::MissingHasher(alg.to_owned()))
+  }
+}
+
+impl std::fmt::Debug for HasherRegistry {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_set().entries(self.hashers.keys()).finish()
+  }
+}
+
+/// Minimum number of disclosures for which digests are computed in parallel, hashing fewer disclosures is
+/// faster than distributing them across threads.
+#[cfg(feature = "parallel")]
+const PARALLEL_THRESHOLD: usize = 64;
+
+/// Computes the [`Hasher::encoded_digest`] of every disclosure, in the order of `disclosures`.
+///
+/// With the `parallel` feature enabled, large sets of disclosures are hashed on the rayon thread pool.
+pub(crate) fn encoded_digests(hasher: &dyn Hasher, disclosures: &[String]) -> Vec<String> {
+  #[cfg(feature = "parallel")]
+  if disclosures.len() >= PARALLEL_THRESHOLD {
+    use rayon::prelude::*;
+    return disclosures
+      .par_iter()
+      .map(|disclosure| hasher.encoded_digest(disclosure))
+      .collect();
+  }
+  disclosures
+    .iter()
+    .map(|disclosure| hasher.encoded_digest(disclosure))
+    .collect()
+}
+
+/// Compares two digests without branching on their content.
+///
+/// Every byte is combined into one accumulator instead of returning at the first difference. This is a best-effort
+/// measure: without an optimization barrier, the compiler is free to short-circuit the fold, so the comparison is
+/// not guaranteed to run in constant time. The lengths of `a` and `b` are not considered secret.
+pub fn digests_equal(a: &str, b: &str) -> bool {
+  let (a, b) = (a.as_bytes(), b.as_bytes());
+  if a.len() != b.len() {
+    return false;
+  }
+  a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
 // Some test values taken from https://www.ietf.org/archive/id/draft-ietf-oauth-selective-disclosure-jwt-07.html#name-disclosures
 #[cfg(test)]
 mod test {
+  use super::encoded_digests;
+  use super::HasherRegistry;
+  use crate::digests_equal;
+  use crate::Error;
   use crate::Hasher;
   use crate::Sha256Hasher;
 
@@ -90,4 +302,66 @@ mod test {
     let hash = hasher.encoded_digest(disclosure);
     assert_eq!("w0I8EKcdCtUPkGCNUrfwVp2xEgNjtoIDlOxc9-PlOhs", hash);
   }
+
+  #[test]
+  fn test_digests_equal() {
+    let digest = "uutlBuYeMDyjLLTpf6Jxi7yNkEF35jdyWMn9U7b_RYY";
+    assert!(digests_equal(digest, digest));
+    assert!(!digests_equal(digest, "uutlBuYeMDyjLLTpf6Jxi7yNkEF35jdyWMn9U7b_RYZ"));
+    assert!(!digests_equal(digest, "uutlBuYeMDyjLLTpf6Jxi7yNkEF35jdyWMn9U7b_RY"));
+  }
+
+  #[test]
+  fn test_encoded_digests() {
+    let hasher = Sha256Hasher::new();
+    let disclosures: Vec<String> = (0..200).map(|index| format!("disclosure-{}", index)).collect();
+    let digests = encoded_digests(&hasher, &disclosures);
+    assert_eq!(digests.len(), disclosures.len());
+    for (disclosure, digest) in disclosures.iter().zip(&digests) {
+      assert_eq!(&hasher.encoded_digest(disclosure), digest);
+    }
+  }
+
+  struct FakeHasher(&'static str);
+
+  impl Hasher for FakeHasher {
+    fn digest(&self, input: &[u8]) -> Vec<u8> {
+      input.to_vec()
+    }
+
+    fn alg_name(&self) -> &'static str {
+      self.0
+    }
+  }
+
+  #[cfg(feature = "sha-512")]
+  #[test]
+  fn sha512() {
+    let disclosure = "WyI2cU1RdlJMNWhhaiIsICJmYW1pbHlfbmFtZSIsICJNw7ZiaXVzIl0";
+    let hash = crate::Sha512Hasher::new().encoded_digest(disclosure);
+    assert_eq!(hash.len(), 86);
+    let registry = HasherRegistry::with_available_hashers();
+    assert_eq!(registry.alg_names().collect::<Vec<_>>(), vec!["sha-256", "sha-512"]);
+  }
+
+  #[test]
+  fn registry() {
+    let mut registry = HasherRegistry::with_sha256();
+    assert!(registry.register(Box::new(FakeHasher("sha-512"))).unwrap().is_none());
+    assert!(registry.register(Box::new(FakeHasher("sha-256"))).unwrap().is_some());
+    assert!(matches!(
+      registry.register(Box::new(FakeHasher("SHA256"))).err().unwrap(),
+      Error::UnregisteredHashAlgorithm(alg) if alg == "SHA256"
+    ));
+    assert_eq!(registry.alg_names().collect::<Vec<_>>(), vec!["sha-256", "sha-512"]);
+
+    let object = serde_json::json!({ "_sd_alg": "sha-512" });
+    let object = object.as_object().unwrap();
+    assert_eq!(registry.determine_hasher(object).unwrap().alg_name(), "sha-512");
+    registry.remove("sha-512");
+    assert!(matches!(
+      registry.determine_hasher(object).err().unwrap(),
+      Error::MissingHasher(_)
+    ));
+  }
 }
@@ -0,0 +1,62 @@
+// Copyright 2020-2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::BTreeMap;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+/// Record of how an object was transformed by an [`SdObjectEncoder`](crate::SdObjectEncoder), returned by
+/// [`SdObjectEncoder::trace`](crate::SdObjectEncoder::trace).
+///
+/// Issuers can store it for audits and dispute resolution, e.g. to prove which digest of an issued token
+/// belongs to which claim, without having to keep the disclosures in their encoded form.
+///
+/// ## Note
+///
+/// The salts allow to verify guesses of the concealed values against the digests, so the trace must be
+/// stored as confidentially as the disclosures themselves.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "debug-plaintext", derive(Debug))]
+pub struct IssuanceTrace {
+  /// The hash algorithm of the digests, see [`Hasher::alg_name`](crate::Hasher::alg_name).
+  pub sd_alg: String,
+  /// The concealed values, in the order they were concealed.
+  pub disclosures: Vec<TracedDisclosure>,
+  /// The decoy digests, by the JSON pointer of the object or array they were added to.
+  pub decoys: BTreeMap<String, Vec<String>>,
+}
+
+/// A concealed value of an [`IssuanceTrace`].
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "debug-plaintext", derive(Debug))]
+pub struct TracedDisclosure {
+  /// The JSON pointer the value was concealed at.
+  pub path: String,
+  /// The salt of the disclosure.
+  pub salt: String,
+  /// The digest replacing the value in the object.
+  pub digest: String,
+}
+
+#[cfg(not(feature = "debug-plaintext"))]
+impl std::fmt::Debug for IssuanceTrace {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("IssuanceTrace")
+      .field("sd_alg", &self.sd_alg)
+      .field("disclosures", &self.disclosures)
+      .field("decoys", &self.decoys)
+      .finish()
+  }
+}
+
+#[cfg(not(feature = "debug-plaintext"))]
+impl std::fmt::Debug for TracedDisclosure {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("TracedDisclosure")
+      .field("path", &self.path)
+      .field("salt", &crate::disclosure::Redacted)
+      .field("digest", &self.digest)
+      .finish()
+  }
+}
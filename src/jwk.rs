@@ -0,0 +1,206 @@
+// Copyright 2020-2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use serde::Deserialize;
+use serde::Serialize;
+use serde_json::Map;
+use serde_json::Value;
+
+use crate::Error;
+use crate::Hasher;
+
+/// A JSON Web Key as defined in [RFC 7517](https://www.rfc-editor.org/rfc/rfc7517.html).
+///
+/// On construction `kty` and, for the key types `EC`, `RSA`, `oct` and `OKP`, the members of the public
+/// or symmetric key are validated to be strings, as are `alg`, `use` and `kid` if present. All other
+/// parameters are kept as they are.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "Map<String, Value>", into = "Map<String, Value>")]
+pub struct Jwk {
+  params: Map<String, Value>,
+}
+
+impl Jwk {
+  /// Returns the key type, e.g. `EC` or `OKP`.
+  pub fn kty(&self) -> &str {
+    // `kty` is checked to be a string on construction.
+    self.params.get("kty").and_then(Value::as_str).unwrap_or_default()
+  }
+
+  /// Returns the curve of an elliptic curve key, e.g. `P-256` or `Ed25519`, if present.
+  pub fn crv(&self) -> Option<&str> {
+    self.params.get("crv").and_then(Value::as_str)
+  }
+
+  /// Returns the algorithm the key is intended for, if present.
+  pub fn alg(&self) -> Option<&str> {
+    self.params.get("alg").and_then(Value::as_str)
+  }
+
+  /// Returns the key ID, if present.
+  pub fn kid(&self) -> Option<&str> {
+    self.params.get("kid").and_then(Value::as_str)
+  }
+
+  /// Returns the intended use of the key, `sig` or `enc`, if present.
+  pub fn key_use(&self) -> Option<&str> {
+    self.params.get("use").and_then(Value::as_str)
+  }
+
+  /// Returns the x coordinate of an `EC` or `OKP` key, if present.
+  pub fn x(&self) -> Option<&str> {
+    self.params.get("x").and_then(Value::as_str)
+  }
+
+  /// Returns the y coordinate of an `EC` key, if present.
+  pub fn y(&self) -> Option<&str> {
+    self.params.get("y").and_then(Value::as_str)
+  }
+
+  /// Returns the modulus of an `RSA` key, if present.
+  pub fn n(&self) -> Option<&str> {
+    self.params.get("n").and_then(Value::as_str)
+  }
+
+  /// Returns the exponent of an `RSA` key, if present.
+  pub fn e(&self) -> Option<&str> {
+    self.params.get("e").and_then(Value::as_str)
+  }
+
+  /// Computes the [RFC 7638](https://www.rfc-editor.org/rfc/rfc7638.html) thumbprint of the key, the
+  /// base64url-encoded digest of its required members, using `hasher`.
+  ///
+  /// ## Error
+  /// Returns [`Error::DeserializationError`] if the key type is not `EC`, `RSA`, `oct` or `OKP` or a
+  /// required member is missing or not a string.
+  pub fn thumbprint(&self, hasher: &dyn Hasher) -> Result<String, Error> {
+    let members = required_members(self.kty()).ok_or_else(|| {
+      Error::DeserializationError(format!("JWK thumbprint of key type {} is not supported", self.kty()))
+    })?;
+    let mut canonical = Vec::with_capacity(members.len());
+    for member in members {
+      let value = self
+        .params
+        .get(*member)
+        .filter(|value| value.is_string())
+        .ok_or_else(|| Error::DeserializationError(format!("JWK has no `{}` string", member)))?;
+      canonical.push(format!("\"{}\":{}", member, value));
+    }
+    Ok(hasher.encoded_digest(&format!("{{{}}}", canonical.join(","))))
+  }
+
+  /// Returns all parameters of the key.
+  pub fn params(&self) -> &Map<String, Value> {
+    &self.params
+  }
+
+  /// Returns all parameters of the key, consuming it.
+  pub fn into_params(self) -> Map<String, Value> {
+    self.params
+  }
+}
+
+impl TryFrom<Map<String, Value>> for Jwk {
+  type Error = Error;
+
+  fn try_from(params: Map<String, Value>) -> Result<Self, Self::Error> {
+    let kty = params
+      .get("kty")
+      .and_then(Value::as_str)
+      .ok_or_else(|| Error::DeserializationError("JWK has no `kty` string".to_string()))?;
+    for member in required_members(kty).unwrap_or_default() {
+      if !params.get(*member).map_or(false, Value::is_string) {
+        return Err(Error::DeserializationError(format!(
+          "JWK of key type {} has no `{}` string",
+          kty, member
+        )));
+      }
+    }
+    for member in ["alg", "use", "kid"] {
+      if params.get(member).map_or(false, |value| !value.is_string()) {
+        return Err(Error::DeserializationError(format!(
+          "JWK member `{}` is not a string",
+          member
+        )));
+      }
+    }
+    Ok(Self { params })
+  }
+}
+
+/// Returns the required members of the key type `kty` in lexicographic order, as used for thumbprints, or
+/// `None` if the key type is not known.
+fn required_members(kty: &str) -> Option<&'static [&'static str]> {
+  match kty {
+    "EC" => Some(&["crv", "kty", "x", "y"]),
+    "RSA" => Some(&["e", "kty", "n"]),
+    "oct" => Some(&["k", "kty"]),
+    "OKP" => Some(&["crv", "kty", "x"]),
+    _ => None,
+  }
+}
+
+impl From<Jwk> for Map<String, Value> {
+  fn from(jwk: Jwk) -> Self {
+    jwk.params
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::Jwk;
+  use serde_json::json;
+
+  #[test]
+  fn accessors() {
+    let jwk: Jwk = serde_json::from_value(json!({
+      "kty": "EC",
+      "crv": "P-256",
+      "kid": "key-1",
+      "x": "TCAER19Zvu3OHF4j4W4vfSVoHIP1ILilDls7vCeGemc",
+      "y": "ZxjiWWbZMQGHVWKVQ4hbSIirsVfuecCE6t4jT9F2HZQ"
+    }))
+    .unwrap();
+    assert_eq!(jwk.kty(), "EC");
+    assert_eq!(jwk.crv(), Some("P-256"));
+    assert_eq!(jwk.kid(), Some("key-1"));
+    assert_eq!(jwk.alg(), None);
+  }
+
+  #[cfg(feature = "sha")]
+  #[test]
+  fn thumbprint() {
+    // Example of RFC 7638, section 3.1.
+    let jwk: Jwk = serde_json::from_value(json!({
+      "kty": "RSA",
+      "n": "0vx7agoebGcQSuuPiLJXZptN9nndrQmbXEps2aiAFbWhM78LhWx4cbbfAAtVT86zwu1RK7aPFFxuhDR1L6tSoc_BJECPebWKRXjBZCiFV4n3oknjhMstn64tZ_2W-5JsGY4Hc5n9yBXArwl93lqt7_RN5w6Cf0h4QyQ5v-65YGjQR0_FDW2QvzqY368QQMicAtaSqzs8KJZgnYb9c7d0zgdAZHzu6qMQvRL5hajrn1n91CbOpbISD08qNLyrdkt-bFTWhAI4vMQFh6WeZu0fM4lFd2NcRwr3XPksINHaQ-G_xBniIqbw0Ls1jF44-csFCur-kEgU8awapJzKnqDKgw",
+      "e": "AQAB",
+      "alg": "RS256",
+      "kid": "2011-04-29"
+    }))
+    .unwrap();
+    assert_eq!(
+      jwk.thumbprint(&crate::Sha256Hasher::new()).unwrap(),
+      "NzbLsXh8uDCcd-6MNwXF4W_7noWXFZAfHkxZsRGC9Xs"
+    );
+  }
+
+  #[test]
+  fn missing_kty() {
+    assert!(serde_json::from_value::<Jwk>(json!({ "crv": "P-256" })).is_err());
+  }
+
+  #[test]
+  fn malformed_members() {
+    assert!(serde_json::from_value::<Jwk>(json!({ "kty": "EC", "crv": "P-256", "x": "x" })).is_err());
+    assert!(serde_json::from_value::<Jwk>(json!({ "kty": "RSA", "n": "n", "e": 65537 })).is_err());
+    assert!(serde_json::from_value::<Jwk>(json!({ "kty": "OKP", "crv": "Ed25519", "x": "x", "use": 1 })).is_err());
+    let jwk: Jwk = serde_json::from_value(json!({ "kty": "RSA", "n": "n", "e": "AQAB", "use": "sig" })).unwrap();
+    assert_eq!(
+      (jwk.n(), jwk.e(), jwk.key_use()),
+      (Some("n"), Some("AQAB"), Some("sig"))
+    );
+    // Members of unknown key types are not validated.
+    assert!(serde_json::from_value::<Jwk>(json!({ "kty": "AKP", "pub": 1 })).is_ok());
+  }
+}
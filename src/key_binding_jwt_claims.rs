@@ -1,10 +1,17 @@
 // Copyright 2020-2024 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::AsyncHasher;
+use crate::Error;
 use crate::Hasher;
-use itertools::Itertools;
+use crate::NonceStore;
+use crate::Result;
+use crate::SdJwt;
+use crate::SpecVersion;
+use serde::de::DeserializeOwned;
 use serde::Deserialize;
 use serde::Serialize;
+use serde_json::Map;
 use serde_json::Value;
 use std::collections::BTreeMap;
 
@@ -15,24 +22,310 @@ pub struct KeyBindingJwtClaims {
   pub aud: String,
   pub nonce: String,
   pub sd_hash: String,
+  /// Expiration time, limits the validity of short-lived key binding JWTs.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub exp: Option<i64>,
+  /// Time before which the key binding JWT must not be accepted.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub nbf: Option<i64>,
+  /// Unique identifier of the key binding JWT, e.g. to track replays.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub jti: Option<String>,
   #[serde(flatten)]
   pub properties: BTreeMap<String, Value>,
 }
 
 impl KeyBindingJwtClaims {
-  pub const KB_JWT_HEADER_TYP: &'static str = " kb+jwt";
+  /// `typ` header value of the key binding JWT.
+  pub const KB_JWT_HEADER_TYP: &'static str = "kb+jwt";
+  const RESERVED_CLAIMS: [&'static str; 7] = ["iat", "aud", "nonce", "sd_hash", "exp", "nbf", "jti"];
 
   /// Creates a new [`KeyBindingJwtClaims`].
+  ///
+  /// `sd_hash` is computed as described in [`SdJwt::sd_hash`].
   pub fn new(hasher: &dyn Hasher, jwt: String, disclosures: Vec<String>, nonce: String, aud: String, iat: i64) -> Self {
-    let disclosures = disclosures.iter().join("~");
-    let sd_jwt = format!("{}~{}~", jwt, disclosures);
-    let hash = hasher.encoded_digest(&sd_jwt);
+    let hash = SdJwt::new(jwt, disclosures, None).sd_hash(hasher);
+    Self::with_sd_hash(hash, nonce, aud, iat)
+  }
+
+  /// Same as [`KeyBindingJwtClaims::new`], computing `sd_hash` with an [`AsyncHasher`].
+  pub async fn new_async(
+    hasher: &dyn AsyncHasher,
+    jwt: String,
+    disclosures: Vec<String>,
+    nonce: String,
+    aud: String,
+    iat: i64,
+  ) -> Self {
+    let hash = SdJwt::new(jwt, disclosures, None).sd_hash_async(hasher).await;
+    Self::with_sd_hash(hash, nonce, aud, iat)
+  }
+
+  fn with_sd_hash(hash: String, nonce: String, aud: String, iat: i64) -> Self {
     Self {
       iat,
       aud,
       nonce,
       sd_hash: hash,
+      exp: None,
+      nbf: None,
+      jti: None,
       properties: BTreeMap::new(),
     }
   }
+
+  /// Inserts an additional claim, e.g. a transaction data hash, and returns the previous value of the
+  /// claim, if any.
+  ///
+  /// ## Error
+  /// Returns [`Error::ClaimCollisionError`] if `name` is one of the claims represented by a dedicated
+  /// field, i.e. `iat`, `aud`, `nonce`, `sd_hash`, `exp`, `nbf` or `jti`.
+  pub fn insert_property(&mut self, name: impl Into<String>, value: Value) -> Result<Option<Value>> {
+    let name = name.into();
+    if Self::RESERVED_CLAIMS.contains(&name.as_str()) {
+      return Err(Error::ClaimCollisionError {
+        path: String::new(),
+        claim_name: name,
+      });
+    }
+    Ok(self.properties.insert(name, value))
+  }
+
+  /// Deserializes the additional claim `name` into `T`, returns `None` if the claim is not present.
+  ///
+  /// ## Error
+  /// Returns [`Error::DataTypeMismatch`] if the claim can not be deserialized into `T`.
+  pub fn get<T: DeserializeOwned>(&self, name: &str) -> Result<Option<T>> {
+    self
+      .properties
+      .get(name)
+      .map(|value| {
+        T::deserialize(value).map_err(|err| Error::DataTypeMismatch {
+          path: crate::pointer::join_pointer("", name),
+          reason: err.to_string(),
+        })
+      })
+      .transpose()
+  }
+
+  /// Serializes the claims as defined by `version`, e.g. with `_sd_hash` instead of `sd_hash` for
+  /// [`SpecVersion::Draft07`].
+  ///
+  /// ## Error
+  /// Returns [`Error::DeserializationError`] if the claims can not be serialized.
+  pub fn to_object_for(&self, version: SpecVersion) -> Result<Map<String, Value>> {
+    let mut object = match serde_json::to_value(self) {
+      Ok(Value::Object(object)) => object,
+      Ok(_) => return Err(Error::DeserializationError("claims are not an object".to_string())),
+      Err(err) => return Err(Error::DeserializationError(err.to_string())),
+    };
+    if let Some(sd_hash) = crate::encoder::remove_property(&mut object, "sd_hash") {
+      object.insert(version.sd_hash_claim().to_owned(), sd_hash);
+    }
+    Ok(object)
+  }
+
+  /// Deserializes claims as defined by `version`.
+  ///
+  /// ## Error
+  /// Returns [`Error::DeserializationError`] if `object` is not a valid claims set, e.g. if the digest is
+  /// in the claim of another version.
+  pub fn from_object_for(mut object: Map<String, Value>, version: SpecVersion) -> Result<Self> {
+    if version.sd_hash_claim() != "sd_hash" {
+      if object.contains_key("sd_hash") {
+        return Err(Error::DeserializationError(format!(
+          "`sd_hash` is not defined in {:?}",
+          version
+        )));
+      }
+      if let Some(sd_hash) = crate::encoder::remove_property(&mut object, version.sd_hash_claim()) {
+        object.insert("sd_hash".to_owned(), sd_hash);
+      }
+    }
+    serde_json::from_value(Value::Object(object)).map_err(|err| Error::DeserializationError(err.to_string()))
+  }
+
+  /// Consumes the `nonce` of these claims from `store`, rejecting replayed key binding JWTs. See
+  /// [`SdJwt::validate_full_with_nonce_store`](crate::SdJwt::validate_full_with_nonce_store) to do so
+  /// while validating a presentation.
+  ///
+  /// `now` is the current time in seconds since the Unix epoch.
+  ///
+  /// ## Error
+  /// Returns [`Error::InvalidNonce`] if the nonce was not issued by `store`, has expired or was already
+  /// consumed.
+  pub fn consume_nonce(&self, store: &dyn NonceStore, now: i64) -> Result<()> {
+    if store.consume(&self.nonce, now) {
+      Ok(())
+    } else {
+      Err(Error::InvalidNonce(self.nonce.clone()))
+    }
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::KeyBindingJwtClaims;
+  use crate::test_utils;
+  use crate::Error;
+  use crate::InMemoryNonceStore;
+  use crate::NonceStore;
+  use crate::SpecVersion;
+  use serde_json::json;
+
+  #[test]
+  fn exp_and_nbf() {
+    let mut claims = KeyBindingJwtClaims {
+      iat: 1698077790,
+      aud: "https://verifier.example.org".to_string(),
+      nonce: "1234567890".to_string(),
+      sd_hash: "u1zsNLFQxeVEFpTfOVu4TcI3Zb7C_U3a1E4esAYQ-zY".to_string(),
+      ..Default::default()
+    };
+    let value = serde_json::to_value(&claims).unwrap();
+    assert!(value.get("exp").is_none());
+    assert!(value.get("nbf").is_none());
+    assert_eq!(serde_json::from_value::<KeyBindingJwtClaims>(value).unwrap(), claims);
+
+    claims.exp = Some(1698078090);
+    claims.nbf = Some(1698077790);
+    let value = serde_json::to_value(&claims).unwrap();
+    assert_eq!(value["exp"], json!(1698078090));
+    let deserialized: KeyBindingJwtClaims = serde_json::from_value(value).unwrap();
+    assert_eq!(deserialized, claims);
+    assert!(deserialized.properties.is_empty());
+  }
+
+  #[test]
+  fn insert_property() {
+    let mut claims = KeyBindingJwtClaims::default();
+    assert_eq!(claims.insert_property("txn", json!("c2a8f6d4")).unwrap(), None);
+    assert_eq!(
+      claims.insert_property("txn", json!("7d1e3b90")).unwrap(),
+      Some(json!("c2a8f6d4"))
+    );
+    assert!(matches!(
+      claims.insert_property("nonce", json!("1234567890")).unwrap_err(),
+      Error::ClaimCollisionError { .. }
+    ));
+    assert!(matches!(
+      claims.insert_property("jti", json!("7d1e3b90")).unwrap_err(),
+      Error::ClaimCollisionError { .. }
+    ));
+    assert_eq!(serde_json::to_value(&claims).unwrap()["txn"], json!("7d1e3b90"));
+  }
+
+  #[test]
+  fn jti_and_get() {
+    let claims: KeyBindingJwtClaims = serde_json::from_value(json!({
+      "iat": 1698077790,
+      "aud": "https://verifier.example.org",
+      "nonce": "1234567890",
+      "sd_hash": "u1zsNLFQxeVEFpTfOVu4TcI3Zb7C_U3a1E4esAYQ-zY",
+      "jti": "c2a8f6d4",
+      "transaction_data_hashes": ["fOBUSQvo46yQO-wRwXBcGqvnbKIueISEL961_Sjd4do"]
+    }))
+    .unwrap();
+    assert_eq!(claims.jti.as_deref(), Some("c2a8f6d4"));
+    assert!(!claims.properties.contains_key("jti"));
+    let hashes: Option<Vec<String>> = claims.get("transaction_data_hashes").unwrap();
+    assert_eq!(hashes.unwrap().len(), 1);
+    assert_eq!(claims.get::<String>("missing").unwrap(), None);
+    assert!(matches!(
+      claims.get::<i64>("transaction_data_hashes").unwrap_err(),
+      Error::DataTypeMismatch { path, .. } if path == "/transaction_data_hashes"
+    ));
+  }
+
+  #[test]
+  fn consume_nonce() {
+    let store = InMemoryNonceStore::new();
+    store.issue("1234567890", 1698077850);
+    let claims = KeyBindingJwtClaims {
+      nonce: "1234567890".to_string(),
+      ..Default::default()
+    };
+    claims.consume_nonce(&store, 1698077790).unwrap();
+    // Replaying the key binding JWT fails.
+    assert!(matches!(
+      claims.consume_nonce(&store, 1698077790).unwrap_err(),
+      Error::InvalidNonce(_)
+    ));
+  }
+
+  #[test]
+  fn spec_version() {
+    let claims = KeyBindingJwtClaims {
+      sd_hash: "u1zsNLFQxeVEFpTfOVu4TcI3Zb7C_U3a1E4esAYQ-zY".to_string(),
+      ..Default::default()
+    };
+    let draft07 = claims.to_object_for(SpecVersion::Draft07).unwrap();
+    assert_eq!(draft07["_sd_hash"], json!(claims.sd_hash));
+    assert!(draft07.get("sd_hash").is_none());
+    assert_eq!(
+      KeyBindingJwtClaims::from_object_for(draft07.clone(), SpecVersion::Draft07).unwrap(),
+      claims
+    );
+    assert!(KeyBindingJwtClaims::from_object_for(draft07, SpecVersion::Draft08).is_err());
+
+    let draft08 = claims.to_object_for(SpecVersion::default()).unwrap();
+    assert_eq!(serde_json::to_value(&claims).unwrap(), json!(draft08));
+    assert!(KeyBindingJwtClaims::from_object_for(draft08, SpecVersion::Draft07).is_err());
+  }
+
+  #[cfg(feature = "sha")]
+  #[test]
+  fn async_hasher() {
+    use crate::AsyncHasher;
+    use crate::DigestFuture;
+    use crate::Hasher;
+    use crate::Sha256Hasher;
+    use std::future::Future;
+    use std::sync::Arc;
+    use std::task::Context;
+    use std::task::Poll;
+    use std::task::Wake;
+
+    struct RemoteHasher;
+
+    impl AsyncHasher for RemoteHasher {
+      fn digest<'a>(&'a self, input: &'a [u8]) -> DigestFuture<'a> {
+        Box::pin(async move { Sha256Hasher::new().digest(input) })
+      }
+
+      fn alg_name(&self) -> &'static str {
+        "sha-256"
+      }
+    }
+
+    struct NoopWaker;
+
+    impl Wake for NoopWaker {
+      fn wake(self: Arc<Self>) {}
+    }
+
+    let jwt = test_utils::jwt(json!({ "alg": "ES256" }), "{}");
+    let disclosures = vec!["WyJsa2x4RjVqTVlsR1RQVW92TU5JdkNBIiwgIlVTIl0".to_owned()];
+    let mut future = Box::pin(KeyBindingJwtClaims::new_async(
+      &RemoteHasher,
+      jwt.clone(),
+      disclosures.clone(),
+      "nonce".to_owned(),
+      "aud".to_owned(),
+      0,
+    ));
+    let waker = Arc::new(NoopWaker).into();
+    let Poll::Ready(claims) = future.as_mut().poll(&mut Context::from_waker(&waker)) else {
+      panic!("the digest of the remote hasher is computed immediately");
+    };
+    let expected = KeyBindingJwtClaims::new(
+      &Sha256Hasher::new(),
+      jwt,
+      disclosures,
+      "nonce".to_owned(),
+      "aud".to_owned(),
+      0,
+    );
+    assert_eq!(claims, expected);
+  }
 }
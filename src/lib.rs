@@ -1,21 +1,70 @@
 // Copyright 2020-2023 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
+mod base64;
+mod canonical;
+mod claim_checks;
+mod claim_path;
+mod claims_requirements;
 mod decoder;
+mod did;
 mod disclosure;
 mod encoder;
 mod error;
+#[cfg(feature = "ffi")]
+pub mod ffi;
 mod hasher;
+mod issuance_trace;
+mod jwk;
+mod jwt;
 mod key_binding_jwt_claims;
+mod lint;
+mod nonce;
+mod openid4vci;
+mod openid4vp;
+mod pointer;
+mod presentation_bundle;
+mod profile;
+mod required_key_binding;
 mod sd_jwt;
+mod spec_version;
+mod stored_sd_jwt;
+#[cfg(test)]
+mod test_utils;
+#[cfg(feature = "test-vectors")]
+pub mod test_vectors;
+mod typ;
+mod typed_object;
+mod validation;
 
+pub use base64::set_base64_engine;
+pub use base64::Base64Engine;
+pub use base64::DefaultBase64Engine;
+pub use canonical::*;
+pub use claim_checks::*;
+pub use claim_path::*;
+pub use claims_requirements::*;
 pub use decoder::*;
 pub use disclosure::*;
 pub use encoder::*;
 pub use error::*;
 pub use hasher::*;
+pub use issuance_trace::*;
+pub use jwk::*;
+pub use jwt::*;
 pub use key_binding_jwt_claims::*;
+pub use lint::*;
+pub use nonce::*;
+pub use openid4vci::*;
+pub use openid4vp::*;
+pub use presentation_bundle::*;
+pub use profile::*;
+pub use required_key_binding::*;
 pub use sd_jwt::*;
 pub use serde_json::json;
 pub use serde_json::Map;
 pub use serde_json::Value;
+pub use spec_version::*;
+pub use stored_sd_jwt::*;
+pub use typ::*;
+pub use validation::*;
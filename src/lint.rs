@@ -0,0 +1,400 @@
+// Copyright 2020-2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use serde::Deserialize;
+use serde::Serialize;
+use serde_json::Map;
+use serde_json::Value;
+
+use crate::hasher::encoded_digests;
+use crate::pointer::join_pointer;
+use crate::Disclosure;
+use crate::Hasher;
+use crate::Result;
+use crate::SdJwt;
+use crate::Severity;
+use crate::ARRAY_DIGEST_KEY;
+use crate::DIGESTS_KEY;
+use crate::MIN_SALT_SIZE;
+use crate::SD_ALG;
+
+/// A rule of the specification checked by [`SdJwt::lint`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum LintRule {
+  /// Decoy digests SHOULD be added to hide the number of selectively disclosable claims.
+  Decoys,
+  /// The original order of the digests in an `_sd` array MUST be hidden, e.g. by sorting or shuffling.
+  DigestOrder,
+  /// Disclosures MUST NOT use the claim names `_sd` and `...`.
+  ReservedClaimName,
+  /// `_sd_alg` MUST only appear at the top level of the payload.
+  SdAlgPlacement,
+  /// Salts SHOULD contain at least 128 bits of randomness.
+  SaltLength,
+}
+
+/// A violated rule found by [`SdJwt::lint`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LintFinding {
+  /// The violated rule.
+  pub rule: LintRule,
+  /// [`Severity::Error`] for MUST rules, [`Severity::Warning`] for SHOULD rules and for the heuristic
+  /// [`LintRule::DigestOrder`].
+  pub severity: Severity,
+  /// JSON pointer to the location of the violation in the issuer-signed JWT's claims or a disclosure's
+  /// value, if it applies to a single location.
+  pub path: Option<String>,
+  /// Description of the violation.
+  pub message: String,
+}
+
+impl SdJwt {
+  /// Checks this SD-JWT against SHOULD and MUST rules of the specification for issuers, e.g. in CI, and
+  /// returns all findings, empty if the SD-JWT complies.
+  ///
+  /// The SD-JWT is expected to contain all disclosures, as issued. The rules are listed by [`LintRule`].
+  ///
+  /// ## Note
+  /// The digest order is flagged if the digests of an `_sd` array are in the order of their disclosures,
+  /// which hints at digests being appended as claims are concealed. Sort the arrays before signing. As a few
+  /// digests can be in that order by chance, the finding is a [`Severity::Warning`].
+  ///
+  /// ## Error
+  /// * [`Error::DeserializationError`](crate::Error::DeserializationError) if the issuer-signed JWT can not
+  ///   be parsed.
+  /// * [`Error::InvalidDisclosure`](crate::Error::InvalidDisclosure) if a disclosure can not be parsed.
+  pub fn lint(&self, hasher: &dyn Hasher) -> Result<Vec<LintFinding>> {
+    let claims = self.parse_jwt()?.into_claims();
+    let disclosures = self
+      .disclosures
+      .iter()
+      .filter(|disclosure| !disclosure.is_empty())
+      .map(|disclosure| Disclosure::parse_with_min_salt_size(disclosure.clone(), 0))
+      .collect::<Result<Vec<_>>>()?;
+    let encoded: Vec<String> = disclosures.iter().map(|disclosure| disclosure.to_string()).collect();
+    let digests = encoded_digests(hasher, &encoded);
+
+    let mut lint = Lint {
+      digests: &digests,
+      findings: vec![],
+      digest_count: 0,
+    };
+    lint.object(&claims, "", true);
+    for disclosure in &disclosures {
+      if let Some(name) = disclosure.claim_name.as_deref() {
+        if name == DIGESTS_KEY || name == ARRAY_DIGEST_KEY {
+          lint.push(
+            LintRule::ReservedClaimName,
+            Severity::Error,
+            None,
+            format!("disclosure uses the reserved claim name `{}`", name),
+          );
+        }
+      }
+      if let Err(err) = Disclosure::validate_salt(&disclosure.salt, MIN_SALT_SIZE) {
+        lint.push(LintRule::SaltLength, Severity::Warning, None, err.to_string());
+      }
+      lint.value(&disclosure.claim_value, "");
+    }
+    if lint.digest_count <= digests.len() {
+      lint.push(
+        LintRule::Decoys,
+        Severity::Warning,
+        None,
+        "there is no digest without disclosure, decoys should be added",
+      );
+    }
+    Ok(lint.findings)
+  }
+}
+
+/// A linkability risk found by [`privacy_lint`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum PrivacyRisk {
+  /// The same salt is used by several disclosures, so their digests can be correlated.
+  ReusedSalt,
+  /// The same digest appears in several credentials, which makes them linkable.
+  SharedDigest,
+  /// A salt is too short or too uniform to prevent guessing the disclosed value from its digest.
+  LowEntropySalt,
+}
+
+/// A finding of [`privacy_lint`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PrivacyFinding {
+  /// The detected risk.
+  pub risk: PrivacyRisk,
+  /// Indices of the affected credentials in the analyzed slice.
+  pub credentials: Vec<usize>,
+  /// Description of the finding.
+  pub message: String,
+}
+
+/// Analyzes the SD-JWTs stored by a holder for issuer practices that undermine unlinkability: salts reused
+/// by several disclosures, digests shared by several credentials and low-entropy salts.
+///
+/// ## Note
+/// Findings do not contain salts or digests, only the indices of the affected `credentials`.
+///
+/// ## Error
+/// Same as [`SdJwt::lint`].
+pub fn privacy_lint(credentials: &[SdJwt]) -> Result<Vec<PrivacyFinding>> {
+  let mut findings = vec![];
+  // Credentials by salt and by digest, in order of first occurrence.
+  let mut salts: Vec<(String, Vec<usize>)> = vec![];
+  let mut digests: Vec<(String, Vec<usize>)> = vec![];
+  let record = |entries: &mut Vec<(String, Vec<usize>)>, key: &str, index: usize| match entries
+    .iter_mut()
+    .find(|(candidate, _)| candidate == key)
+  {
+    Some((_, indices)) => indices.push(index),
+    None => entries.push((key.to_owned(), vec![index])),
+  };
+
+  for (index, sd_jwt) in credentials.iter().enumerate() {
+    let claims = Value::Object(sd_jwt.parse_jwt()?.into_claims());
+    let mut found = vec![];
+    collect_digests(&claims, &mut found);
+    let mut low_entropy = false;
+    for disclosure in sd_jwt.disclosures.iter().filter(|disclosure| !disclosure.is_empty()) {
+      let disclosure = Disclosure::parse_with_min_salt_size(disclosure.clone(), 0)?;
+      collect_digests(&disclosure.claim_value, &mut found);
+      record(&mut salts, &disclosure.salt, index);
+      let distinct = disclosure.salt.chars().collect::<std::collections::HashSet<_>>().len();
+      low_entropy |= Disclosure::validate_salt(&disclosure.salt, MIN_SALT_SIZE).is_err()
+        || distinct < disclosure.salt.len().min(MIN_SALT_SIZE) / 2;
+    }
+    // Reported once per credential, however many of its salts are weak.
+    if low_entropy {
+      findings.push(PrivacyFinding {
+        risk: PrivacyRisk::LowEntropySalt,
+        credentials: vec![index],
+        message: "a salt is too short or too uniform".to_string(),
+      });
+    }
+    found.sort_unstable();
+    found.dedup();
+    for digest in found {
+      record(&mut digests, &digest, index);
+    }
+  }
+
+  for (_, indices) in salts.into_iter().filter(|(_, indices)| indices.len() > 1) {
+    findings.push(PrivacyFinding {
+      risk: PrivacyRisk::ReusedSalt,
+      credentials: dedup(indices),
+      message: "a salt is used by several disclosures".to_string(),
+    });
+  }
+  for (_, indices) in digests.into_iter().filter(|(_, indices)| indices.len() > 1) {
+    findings.push(PrivacyFinding {
+      risk: PrivacyRisk::SharedDigest,
+      credentials: indices,
+      message: "a digest appears in several credentials".to_string(),
+    });
+  }
+  Ok(findings)
+}
+
+fn dedup(mut indices: Vec<usize>) -> Vec<usize> {
+  indices.dedup();
+  indices
+}
+
+/// Collects the digests of `_sd` arrays and array elements in `value`.
+fn collect_digests(value: &Value, digests: &mut Vec<String>) {
+  match value {
+    Value::Object(object) => {
+      for (key, value) in object {
+        match (key.as_str(), value) {
+          (DIGESTS_KEY, Value::Array(entries)) => {
+            digests.extend(entries.iter().filter_map(Value::as_str).map(str::to_owned))
+          }
+          (ARRAY_DIGEST_KEY, Value::String(digest)) if object.len() == 1 => digests.push(digest.clone()),
+          _ => collect_digests(value, digests),
+        }
+      }
+    }
+    Value::Array(array) => array.iter().for_each(|element| collect_digests(element, digests)),
+    _ => (),
+  }
+}
+
+/// State of [`SdJwt::lint`] while walking the claims and the values of the disclosures.
+struct Lint<'a> {
+  /// Digests of the disclosures, in the order of the disclosures.
+  digests: &'a [String],
+  findings: Vec<LintFinding>,
+  /// Number of digests found in `_sd` arrays and array elements.
+  digest_count: usize,
+}
+
+impl Lint<'_> {
+  fn push(&mut self, rule: LintRule, severity: Severity, path: Option<String>, message: impl Into<String>) {
+    self.findings.push(LintFinding {
+      rule,
+      severity,
+      path,
+      message: message.into(),
+    });
+  }
+
+  fn object(&mut self, object: &Map<String, Value>, path: &str, top_level: bool) {
+    for (key, value) in object {
+      if key == SD_ALG && !top_level {
+        self.push(
+          LintRule::SdAlgPlacement,
+          Severity::Error,
+          Some(join_pointer(path, key)),
+          "`_sd_alg` is not at the top level",
+        );
+      }
+      if key == DIGESTS_KEY {
+        let entries = value.as_array().map(Vec::as_slice).unwrap_or_default();
+        self.digest_count += entries.len();
+        let positions: Vec<usize> = entries
+          .iter()
+          .filter_map(|digest| {
+            self
+              .digests
+              .iter()
+              .position(|candidate| Some(candidate.as_str()) == digest.as_str())
+          })
+          .collect();
+        if positions.len() > 1 && positions.windows(2).all(|pair| pair[0] < pair[1]) {
+          self.push(
+            LintRule::DigestOrder,
+            Severity::Warning,
+            Some(join_pointer(path, key)),
+            "digests are in the order of their disclosures",
+          );
+        }
+      } else {
+        self.value(value, &join_pointer(path, key));
+      }
+    }
+  }
+
+  fn value(&mut self, value: &Value, path: &str) {
+    match value {
+      Value::Object(object) => self.object(object, path, false),
+      Value::Array(array) => {
+        for (index, element) in array.iter().enumerate() {
+          if element
+            .as_object()
+            .map_or(false, |object| object.contains_key(ARRAY_DIGEST_KEY))
+          {
+            self.digest_count += 1;
+          } else {
+            self.value(element, &join_pointer(path, &index.to_string()));
+          }
+        }
+      }
+      _ => (),
+    }
+  }
+}
+
+#[cfg(all(test, feature = "sha"))]
+mod test {
+  use super::LintRule;
+  use super::PrivacyRisk;
+  use crate::test_utils;
+  use crate::Hasher;
+  use crate::SdJwt;
+  use crate::SdObjectEncoder;
+  use crate::Severity;
+  use crate::Sha256Hasher;
+  use serde_json::json;
+  use std::collections::HashSet;
+
+  fn rules(sd_jwt: &SdJwt) -> Vec<LintRule> {
+    let findings = sd_jwt.lint(&Sha256Hasher::new()).unwrap();
+    findings.into_iter().map(|finding| finding.rule).collect()
+  }
+
+  #[test]
+  fn lint() {
+    let mut encoder = SdObjectEncoder::try_from(json!({
+      "given_name": "Erika",
+      "family_name": "Mustermann",
+      "address": { "_sd_alg": "sha-256" }
+    }))
+    .unwrap();
+    // Disclosures with short salts can no longer be created, conceal the claim by hand.
+    let short_salt = crate::base64::encode(r#"["short", "given_name", "Erika"]"#);
+    let object = encoder.object.as_object_mut().unwrap();
+    object.remove("given_name");
+    object.insert(
+      "_sd".to_owned(),
+      json!([Sha256Hasher::new().encoded_digest(&short_salt)]),
+    );
+    let disclosures = vec![short_salt, encoder.conceal("/family_name", None).unwrap().into_string()];
+    let jwt = test_utils::jwt(json!({ "alg": "ES256" }), encoder.try_to_string().unwrap());
+    let sd_jwt = SdJwt::new(jwt, disclosures, None);
+    // The order of the claims depends on the `preserve_order` feature.
+    let found: HashSet<LintRule> = rules(&sd_jwt).into_iter().collect();
+    let expected = [
+      LintRule::DigestOrder,
+      LintRule::SdAlgPlacement,
+      LintRule::SaltLength,
+      LintRule::Decoys,
+    ];
+    assert_eq!(found, expected.into_iter().collect());
+    let findings = sd_jwt.lint(&Sha256Hasher::new()).unwrap();
+    let digest_order = findings.iter().find(|finding| finding.rule == LintRule::DigestOrder);
+    assert_eq!(digest_order.unwrap().severity, Severity::Warning);
+
+    let mut encoder = SdObjectEncoder::try_from(json!({ "given_name": "Erika" })).unwrap();
+    let disclosure = encoder.conceal("/given_name", None).unwrap().into_string();
+    encoder.add_decoys("", 2).unwrap();
+    let jwt = test_utils::jwt(json!({ "alg": "ES256" }), encoder.try_to_string().unwrap());
+    assert!(rules(&SdJwt::new(jwt, vec![disclosure], None)).is_empty());
+  }
+
+  #[test]
+  fn privacy_lint() {
+    let issue_with = |salt: &str, age_salt: Option<&str>| {
+      let mut encoder = SdObjectEncoder::try_from(json!({ "given_name": "Erika", "age": 42 })).unwrap();
+      let disclosures = vec![
+        encoder
+          .conceal("/given_name", Some(salt.to_owned()))
+          .unwrap()
+          .into_string(),
+        encoder
+          .conceal("/age", age_salt.map(ToOwned::to_owned))
+          .unwrap()
+          .into_string(),
+      ];
+      let jwt = test_utils::jwt(json!({ "alg": "ES256" }), encoder.try_to_string().unwrap());
+      SdJwt::new(jwt, disclosures, None)
+    };
+    let issue = |salt: &str| issue_with(salt, None);
+    let salt = "2GLC42sKQveCfGfryNRN9w";
+    let credentials = [issue(salt), issue(salt), issue("aaaaaaaaaaaaaaaaaaaaaaaa")];
+    let findings = super::privacy_lint(&credentials).unwrap();
+    let risks: Vec<(PrivacyRisk, Vec<usize>)> = findings
+      .into_iter()
+      .map(|finding| (finding.risk, finding.credentials))
+      .collect();
+    assert_eq!(
+      risks,
+      vec![
+        (PrivacyRisk::LowEntropySalt, vec![2]),
+        (PrivacyRisk::ReusedSalt, vec![0, 1]),
+        (PrivacyRisk::SharedDigest, vec![0, 1]),
+      ]
+    );
+    assert_eq!(super::privacy_lint(&credentials[1..]).unwrap().len(), 1);
+
+    // A single finding for a credential with several weak salts.
+    let weak = issue_with("aaaaaaaaaaaaaaaaaaaaaaaa", Some("bbbbbbbbbbbbbbbbbbbbbbbb"));
+    let findings = super::privacy_lint(&[weak]).unwrap();
+    assert_eq!(findings.len(), 1);
+    assert_eq!(findings[0].risk, PrivacyRisk::LowEntropySalt);
+  }
+}
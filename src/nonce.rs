@@ -0,0 +1,114 @@
+// Copyright 2020-2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use rand::Rng;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Generates a nonce from `len` bytes of a cryptographically secure random number generator, encoded as
+/// base64url without padding.
+///
+/// ## Note
+///
+/// At least 16 bytes should be used for nonces of key binding JWTs.
+pub fn generate_nonce(len: usize) -> String {
+  let mut bytes = vec![0; len];
+  rand::thread_rng().fill(&mut bytes[..]);
+  crate::base64::encode(bytes)
+}
+
+/// Keeps track of the nonces a verifier issued to holders, so that every nonce is accepted in at most one
+/// key binding JWT.
+///
+/// Times are in seconds since the Unix epoch, like the `iat` claim of the key binding JWT.
+///
+/// ## Note
+///
+/// Implementations are expected to be shared between requests and therefore take `&self`.
+pub trait NonceStore: Sync + Send {
+  /// Registers `nonce` as issued, valid until `expires_at`.
+  fn issue(&self, nonce: &str, expires_at: i64);
+
+  /// Consumes `nonce` and returns whether it was issued and has not expired at `now`.
+  ///
+  /// A nonce can be consumed only once, every further call returns `false`.
+  fn consume(&self, nonce: &str, now: i64) -> bool;
+}
+
+/// An in-memory implementation of [`NonceStore`].
+#[derive(Debug, Default)]
+pub struct InMemoryNonceStore {
+  nonces: Mutex<HashMap<String, i64>>,
+}
+
+impl InMemoryNonceStore {
+  /// Creates a new empty [`InMemoryNonceStore`].
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Removes all nonces that expired before `now` and were never consumed.
+  pub fn purge_expired(&self, now: i64) {
+    self.lock().retain(|_, expires_at| *expires_at >= now);
+  }
+
+  /// Returns the number of nonces that were issued and not consumed or purged yet.
+  pub fn len(&self) -> usize {
+    self.lock().len()
+  }
+
+  /// Returns `true` if there are no outstanding nonces.
+  pub fn is_empty(&self) -> bool {
+    self.len() == 0
+  }
+
+  fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, i64>> {
+    // The map is left consistent by every operation, so it can be used even if another thread panicked.
+    self.nonces.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+  }
+}
+
+impl NonceStore for InMemoryNonceStore {
+  fn issue(&self, nonce: &str, expires_at: i64) {
+    self.lock().insert(nonce.to_owned(), expires_at);
+  }
+
+  fn consume(&self, nonce: &str, now: i64) -> bool {
+    self.lock().remove(nonce).map_or(false, |expires_at| expires_at >= now)
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::generate_nonce;
+  use super::InMemoryNonceStore;
+  use super::NonceStore;
+
+  #[test]
+  fn generated_nonce() {
+    let nonce = generate_nonce(32);
+    assert_eq!(nonce.len(), 43);
+    assert_eq!(multibase::Base::Base64Url.decode(&nonce).unwrap().len(), 32);
+    assert_ne!(nonce, generate_nonce(32));
+  }
+
+  #[test]
+  fn consume_once() {
+    let store = InMemoryNonceStore::new();
+    store.issue("1234567890", 1698077850);
+    assert!(!store.consume("0987654321", 1698077790));
+    assert!(store.consume("1234567890", 1698077790));
+    assert!(!store.consume("1234567890", 1698077790));
+    assert!(store.is_empty());
+  }
+
+  #[test]
+  fn expired() {
+    let store = InMemoryNonceStore::new();
+    store.issue("1234567890", 1698077850);
+    store.issue("0987654321", 1698077950);
+    assert!(!store.consume("1234567890", 1698077851));
+    store.purge_expired(1698077951);
+    assert!(store.is_empty());
+  }
+}
@@ -0,0 +1,115 @@
+// Copyright 2020-2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Credential responses of [OpenID4VCI](https://openid.net/specs/openid-4-verifiable-credential-issuance-1_0.html)
+//! issuing SD-JWTs.
+
+use serde::Deserialize;
+use serde::Deserializer;
+use serde::Serialize;
+use serde::Serializer;
+
+use crate::NonceStore;
+use crate::SdJwt;
+
+/// Size of the random data of a generated `c_nonce`, in bytes.
+const C_NONCE_SIZE: usize = 32;
+
+/// The response of the credential endpoint, e.g. `{ "credentials": [{ "credential": "<JWT>~<Disclosure>~" }] }`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CredentialResponse {
+  /// The issued credentials.
+  pub credentials: Vec<IssuedCredential>,
+  /// Identifier the wallet uses in notifications about the issued credentials.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub notification_id: Option<String>,
+  /// Nonce the wallet must use in the proof of possession of its key in the next credential request.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub c_nonce: Option<String>,
+  /// Lifetime of `c_nonce` in seconds.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub c_nonce_expires_in: Option<i64>,
+}
+
+/// A credential of a [`CredentialResponse`], serialized in compact serialization.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IssuedCredential {
+  /// The issued SD-JWT with all disclosures.
+  #[serde(serialize_with = "serialize_sd_jwt", deserialize_with = "deserialize_sd_jwt")]
+  pub credential: SdJwt,
+}
+
+impl CredentialResponse {
+  /// Creates a [`CredentialResponse`] of `credentials`.
+  pub fn new(credentials: Vec<SdJwt>) -> Self {
+    Self {
+      credentials: credentials
+        .into_iter()
+        .map(|credential| IssuedCredential { credential })
+        .collect(),
+      notification_id: None,
+      c_nonce: None,
+      c_nonce_expires_in: None,
+    }
+  }
+
+  /// Generates a fresh `c_nonce` valid for `expires_in` seconds from `now` and registers it in `store`, so
+  /// that it can be checked with [`NonceStore::consume`] when the wallet presents its key binding proof.
+  ///
+  /// Returns the generated nonce.
+  pub fn issue_c_nonce(&mut self, store: &dyn NonceStore, now: i64, expires_in: i64) -> &str {
+    let nonce = crate::generate_nonce(C_NONCE_SIZE);
+    store.issue(&nonce, now + expires_in);
+    self.c_nonce_expires_in = Some(expires_in);
+    self.c_nonce.insert(nonce)
+  }
+
+  /// Returns the issued SD-JWTs.
+  pub fn sd_jwts(&self) -> impl Iterator<Item = &SdJwt> {
+    self.credentials.iter().map(|issued| &issued.credential)
+  }
+}
+
+fn serialize_sd_jwt<S: Serializer>(sd_jwt: &SdJwt, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+  serializer.collect_str(sd_jwt)
+}
+
+fn deserialize_sd_jwt<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<SdJwt, D::Error> {
+  let sd_jwt = String::deserialize(deserializer)?;
+  SdJwt::parse(&sd_jwt).map_err(serde::de::Error::custom)
+}
+
+#[cfg(test)]
+mod test {
+  use super::CredentialResponse;
+  use crate::test_utils;
+  use crate::InMemoryNonceStore;
+  use crate::NonceStore;
+  use crate::SdJwt;
+  use serde_json::json;
+
+  #[test]
+  fn credential_response() {
+    let sd_jwt = SdJwt::new(
+      test_utils::jwt(json!({ "alg": "ES256" }), "{}"),
+      vec!["WyJhIiwgImIiXQ".to_owned()],
+      None,
+    );
+    let store = InMemoryNonceStore::new();
+    let mut response = CredentialResponse::new(vec![sd_jwt.clone()]);
+    let c_nonce = response.issue_c_nonce(&store, 1000, 300).to_owned();
+
+    let serialized = serde_json::to_value(&response).unwrap();
+    assert_eq!(
+      serialized,
+      json!({
+        "credentials": [{ "credential": format!("{}~WyJhIiwgImIiXQ~", sd_jwt.jwt) }],
+        "c_nonce": c_nonce,
+        "c_nonce_expires_in": 300
+      })
+    );
+    let deserialized: CredentialResponse = serde_json::from_value(serialized).unwrap();
+    assert_eq!(deserialized.sd_jwts().collect::<Vec<_>>(), vec![&sd_jwt]);
+    assert!(store.consume(&c_nonce, 1300));
+  }
+}
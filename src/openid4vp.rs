@@ -0,0 +1,238 @@
+// Copyright 2020-2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Envelopes of SD-JWT presentations in
+//! [OpenID4VP](https://openid.net/specs/openid-4-verifiable-presentations-1_0.html) authorization responses.
+
+use std::collections::BTreeMap;
+
+use serde::Deserialize;
+use serde::Deserializer;
+use serde::Serialize;
+use serde::Serializer;
+use serde_json::Value;
+
+use crate::Error;
+use crate::Result;
+use crate::SdJwt;
+use crate::Typ;
+
+/// A `vp_token` answering a DCQL query: the presentations by the `id` of the credential query they
+/// answer.
+///
+/// Serializes as a JSON object of arrays, e.g. `{ "pid": ["<JWT>~<Disclosure>~<KB-JWT>"] }`. Single
+/// presentations as string, as used by earlier drafts, are accepted when deserializing.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DcqlVpToken {
+  /// Presentations by credential query `id`.
+  pub presentations: BTreeMap<String, Vec<SdJwt>>,
+}
+
+impl DcqlVpToken {
+  /// Adds `presentation` as an answer to the credential query `query_id`.
+  pub fn insert(&mut self, query_id: impl Into<String>, presentation: SdJwt) {
+    self
+      .presentations
+      .entry(query_id.into())
+      .or_default()
+      .push(presentation);
+  }
+
+  /// Returns the presentations answering the credential query `query_id`, empty if there are none.
+  pub fn get(&self, query_id: &str) -> &[SdJwt] {
+    self.presentations.get(query_id).map(Vec::as_slice).unwrap_or_default()
+  }
+}
+
+impl Serialize for DcqlVpToken {
+  fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+    serializer.collect_map(self.presentations.iter().map(|(query_id, presentations)| {
+      let presentations: Vec<String> = presentations.iter().map(SdJwt::presentation).collect();
+      (query_id, presentations)
+    }))
+  }
+}
+
+impl<'de> Deserialize<'de> for DcqlVpToken {
+  fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Presentations {
+      Single(String),
+      Multiple(Vec<String>),
+    }
+
+    let mut presentations = BTreeMap::new();
+    for (query_id, entries) in BTreeMap::<String, Presentations>::deserialize(deserializer)? {
+      let entries = match entries {
+        Presentations::Single(presentation) => vec![presentation],
+        Presentations::Multiple(presentations) => presentations,
+      };
+      let parsed = entries
+        .iter()
+        .map(|presentation| SdJwt::parse(presentation))
+        .collect::<Result<Vec<_>>>()
+        .map_err(serde::de::Error::custom)?;
+      presentations.insert(query_id, parsed);
+    }
+    Ok(Self { presentations })
+  }
+}
+
+/// The `presentation_submission` of a `vp_token` answering a Presentation Exchange definition, see
+/// [`vp_token_with_submission`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PresentationSubmission {
+  /// Identifier of the submission.
+  pub id: String,
+  /// The `id` of the presentation definition the submission answers.
+  pub definition_id: String,
+  /// The location of the presentation of every input descriptor in the `vp_token`.
+  pub descriptor_map: Vec<DescriptorMapEntry>,
+}
+
+/// An entry of [`PresentationSubmission::descriptor_map`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DescriptorMapEntry {
+  /// The `id` of the input descriptor.
+  pub id: String,
+  /// The credential format, e.g. `dc+sd-jwt`.
+  pub format: String,
+  /// JSONPath of the presentation in the `vp_token`, `$` or `$[<index>]`.
+  pub path: String,
+}
+
+/// Creates the `vp_token` and `presentation_submission` answering the Presentation Exchange definition
+/// `definition_id` with `presentations`, pairs of input descriptor `id` and presentation of the format
+/// `format`.
+///
+/// A single presentation is returned as a string at `$`, several presentations as an array.
+pub fn vp_token_with_submission(
+  presentations: &[(String, SdJwt)],
+  submission_id: impl Into<String>,
+  definition_id: impl Into<String>,
+  format: Typ,
+) -> (Value, PresentationSubmission) {
+  let single = presentations.len() == 1;
+  let descriptor_map = presentations
+    .iter()
+    .enumerate()
+    .map(|(index, (id, _))| DescriptorMapEntry {
+      id: id.clone(),
+      format: format.as_str().to_owned(),
+      path: if single {
+        "$".to_owned()
+      } else {
+        format!("$[{}]", index)
+      },
+    })
+    .collect();
+  let vp_token = match presentations {
+    [(_, presentation)] => Value::String(presentation.presentation()),
+    _ => presentations
+      .iter()
+      .map(|(_, presentation)| Value::String(presentation.presentation()))
+      .collect(),
+  };
+  let submission = PresentationSubmission {
+    id: submission_id.into(),
+    definition_id: definition_id.into(),
+    descriptor_map,
+  };
+  (vp_token, submission)
+}
+
+/// Resolves the presentations of `vp_token` listed in `submission`, returning pairs of input descriptor
+/// `id` and presentation in the order of the descriptor map.
+///
+/// ## Error
+/// Returns [`Error::DeserializationError`] if a path is not `$` or `$[<index>]`, does not refer to a
+/// string in `vp_token` or the presentation can not be parsed.
+pub fn parse_vp_token_with_submission(
+  vp_token: &Value,
+  submission: &PresentationSubmission,
+) -> Result<Vec<(String, SdJwt)>> {
+  submission
+    .descriptor_map
+    .iter()
+    .map(|entry| {
+      let token = match entry.path.as_str() {
+        "$" => Some(vp_token),
+        path => path
+          .strip_prefix("$[")
+          .and_then(|index| index.strip_suffix(']'))
+          .and_then(|index| index.parse::<usize>().ok())
+          .and_then(|index| vp_token.get(index)),
+      };
+      let token = token.and_then(Value::as_str).ok_or_else(|| {
+        Error::DeserializationError(format!(
+          "no presentation at {} for input descriptor {}",
+          entry.path, entry.id
+        ))
+      })?;
+      Ok((entry.id.clone(), SdJwt::parse(token)?))
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod test {
+  use super::parse_vp_token_with_submission;
+  use super::vp_token_with_submission;
+  use super::DcqlVpToken;
+  use crate::test_utils;
+  use crate::SdJwt;
+  use crate::Typ;
+  use serde_json::json;
+
+  fn presentation(disclosure: &str) -> SdJwt {
+    SdJwt::new(
+      test_utils::jwt(json!({ "alg": "ES256" }), "{}"),
+      vec![disclosure.to_owned()],
+      Some(test_utils::jwt(json!({ "alg": "ES256" }), "{}")),
+    )
+  }
+
+  #[test]
+  fn dcql() {
+    let mut vp_token = DcqlVpToken::default();
+    vp_token.insert("pid", presentation("WyJhIiwgImIiXQ"));
+    vp_token.insert("pid", presentation("WyJjIiwgImQiXQ"));
+    let serialized = serde_json::to_value(&vp_token).unwrap();
+    assert_eq!(serialized["pid"].as_array().unwrap().len(), 2);
+    assert_eq!(serde_json::from_value::<DcqlVpToken>(serialized).unwrap(), vp_token);
+
+    let single = json!({ "mdl": presentation("WyJhIiwgImIiXQ").presentation() });
+    let parsed: DcqlVpToken = serde_json::from_value(single).unwrap();
+    assert_eq!(parsed.get("mdl").len(), 1);
+    assert!(parsed.get("pid").is_empty());
+  }
+
+  #[test]
+  fn presentation_exchange() {
+    let presentations = vec![
+      ("pid".to_owned(), presentation("WyJhIiwgImIiXQ")),
+      ("mdl".to_owned(), presentation("WyJjIiwgImQiXQ")),
+    ];
+    let (vp_token, submission) = vp_token_with_submission(&presentations, "submission", "definition", Typ::DcSdJwt);
+    assert_eq!(submission.descriptor_map[1].path, "$[1]");
+    assert_eq!(submission.descriptor_map[1].format, "dc+sd-jwt");
+    assert_eq!(
+      parse_vp_token_with_submission(&vp_token, &submission).unwrap(),
+      presentations
+    );
+
+    let (vp_token, submission) =
+      vp_token_with_submission(&presentations[..1], "submission", "definition", Typ::DcSdJwt);
+    assert!(vp_token.is_string());
+    assert_eq!(submission.descriptor_map[0].path, "$");
+    assert_eq!(
+      parse_vp_token_with_submission(&vp_token, &submission).unwrap(),
+      presentations[..1]
+    );
+
+    let mut invalid = submission;
+    invalid.descriptor_map[0].path = "$.vp".to_owned();
+    assert!(parse_vp_token_with_submission(&vp_token, &invalid).is_err());
+  }
+}
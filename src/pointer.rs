@@ -0,0 +1,75 @@
+// Copyright 2020-2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! [JSON pointer](https://datatracker.ietf.org/doc/html/rfc6901) handling shared by encoding, decoding and
+//! presenting, so that escaped claim names (`~0` for `~`, `~1` for `/`) are treated the same everywhere.
+
+use json_pointer::JsonPointer;
+
+use crate::Error;
+use crate::Result;
+
+/// Parses the JSON pointer `path`.
+///
+/// ## Error
+/// Returns [`Error::InvalidPath`] if `path` is not a valid JSON pointer.
+pub(crate) fn parse_pointer(path: &str) -> Result<JsonPointer<String, Vec<String>>> {
+  path.parse::<JsonPointer<_, _>>().map_err(|err| Error::InvalidPath {
+    path: path.to_owned(),
+    reason: format!("{:?}", err),
+  })
+}
+
+/// Returns the unescaped segments of the JSON pointer `path`.
+///
+/// ## Error
+/// Returns [`Error::InvalidPath`] if `path` is not a valid JSON pointer.
+pub(crate) fn pointer_segments(path: &str) -> Result<Vec<String>> {
+  let mut pointer = parse_pointer(path)?;
+  let mut segments = vec![];
+  while let Some(segment) = pointer.pop() {
+    segments.push(segment);
+  }
+  segments.reverse();
+  Ok(segments)
+}
+
+/// Appends the unescaped `segment` to the JSON pointer `parent`.
+pub(crate) fn join_pointer(parent: &str, segment: &str) -> String {
+  format!("{}/{}", parent, segment.replace('~', "~0").replace('/', "~1"))
+}
+
+/// Returns whether the unescaped `segments` of a pointer match those of `pattern`, in which a `*` segment
+/// matches any single segment, e.g. `/nationalities/*` matches `/nationalities/0`.
+pub(crate) fn segments_match(pattern: &[String], segments: &[String]) -> bool {
+  pattern.len() == segments.len()
+    && pattern
+      .iter()
+      .zip(segments)
+      .all(|(expected, segment)| expected == "*" || expected == segment)
+}
+
+#[cfg(test)]
+mod test {
+  use super::join_pointer;
+  use super::pointer_segments;
+  use super::segments_match;
+
+  #[test]
+  fn escaped_segments() {
+    let path = join_pointer(&join_pointer("", "a/b"), "c~d");
+    assert_eq!(path, "/a~1b/c~0d");
+    assert_eq!(pointer_segments(&path).unwrap(), vec!["a/b", "c~d"]);
+    assert_eq!(pointer_segments("").unwrap(), Vec::<String>::new());
+    assert!(pointer_segments("/a~2").is_err());
+    assert!(pointer_segments("a").is_err());
+  }
+
+  #[test]
+  fn wildcard() {
+    let pattern = pointer_segments("/a~1b/*").unwrap();
+    assert!(segments_match(&pattern, &pointer_segments("/a~1b/0").unwrap()));
+    assert!(!segments_match(&pattern, &pointer_segments("/a/b/0").unwrap()));
+    assert!(!segments_match(&pattern, &pointer_segments("/a~1b").unwrap()));
+  }
+}
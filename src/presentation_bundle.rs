@@ -0,0 +1,232 @@
+// Copyright 2020-2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use serde::Deserialize;
+use serde::Deserializer;
+use serde::Serialize;
+use serde::Serializer;
+use serde_json::Value;
+
+use crate::HasherRegistry;
+use crate::SdJwt;
+use crate::Severity;
+use crate::ValidationCheck;
+use crate::ValidationOptions;
+use crate::ValidationReport;
+
+/// Several SD-JWT presentations sent in one response, each with its own key binding JWT over the same
+/// `nonce` and `aud`.
+///
+/// With serde, the bundle (de)serializes as a JSON array of the presentations in compact serialization,
+/// e.g. `["<JWT>~<Disclosure>~<KB-JWT>", ...]`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PresentationBundle {
+  /// The presentations, in the order they were added.
+  pub presentations: Vec<SdJwt>,
+}
+
+impl PresentationBundle {
+  /// Creates a [`PresentationBundle`] of `presentations`.
+  pub fn new(presentations: Vec<SdJwt>) -> Self {
+    Self { presentations }
+  }
+
+  /// Adds a presentation to the bundle.
+  pub fn push(&mut self, presentation: SdJwt) {
+    self.presentations.push(presentation);
+  }
+
+  /// Validates every presentation with [`SdJwt::validate_full`], requiring a key binding JWT over the
+  /// `nonce` and `aud` of `options`, and returns the reports in the order of the presentations.
+  ///
+  /// The hasher of each presentation is looked up in `hashers` by its `_sd_alg`, see
+  /// [`HasherRegistry::determine_hasher`], so presentations of different issuers may use different hash
+  /// algorithms.
+  ///
+  /// If `options` has no `nonce` or `audience`, the key binding JWTs must still agree on it: a presentation
+  /// whose `nonce` or `aud` differs from the one of the first presentation is reported as an error.
+  pub fn validate(&self, hashers: &HasherRegistry, options: &ValidationOptions) -> Vec<ValidationReport> {
+    let options = ValidationOptions {
+      key_binding_required: true,
+      ..options.clone()
+    };
+    let mut reports: Vec<ValidationReport> = self
+      .presentations
+      .iter()
+      .map(|presentation| {
+        let hasher = presentation
+          .parse_jwt()
+          .and_then(|jwt| hashers.determine_hasher(&jwt.into_claims()));
+        match hasher {
+          Ok(hasher) => presentation.validate_full(hasher, &options),
+          Err(error) => {
+            let mut report = ValidationReport::default();
+            report.push(
+              ValidationCheck::Signature,
+              Severity::Warning,
+              "signatures are not verified",
+            );
+            report.push(ValidationCheck::Structure, Severity::Error, error.to_string());
+            report
+          }
+        }
+      })
+      .collect();
+
+    let kb_claims: Vec<Option<_>> = self
+      .presentations
+      .iter()
+      .map(|presentation| {
+        presentation
+          .parse_key_binding_jwt()
+          .ok()
+          .flatten()
+          .map(|jwt| jwt.into_claims())
+      })
+      .collect();
+    for (claim, expected) in [("aud", &options.audience), ("nonce", &options.nonce)] {
+      if expected.is_some() {
+        continue;
+      }
+      let values: Vec<Option<&str>> = kb_claims
+        .iter()
+        .map(|claims| {
+          claims
+            .as_ref()
+            .and_then(|claims| claims.get(claim))
+            .and_then(Value::as_str)
+        })
+        .collect();
+      for (report, value) in reports.iter_mut().zip(&values).skip(1) {
+        if *value != values[0] {
+          report.push(
+            ValidationCheck::KeyBinding,
+            Severity::Error,
+            format!("{} of the key binding JWT differs from the first presentation", claim),
+          );
+        }
+      }
+    }
+    reports
+  }
+
+  /// Returns whether the bundle is not empty and every presentation is valid according to
+  /// [`PresentationBundle::validate`].
+  pub fn is_valid(&self, hashers: &HasherRegistry, options: &ValidationOptions) -> bool {
+    !self.presentations.is_empty() && self.validate(hashers, options).iter().all(ValidationReport::is_valid)
+  }
+}
+
+impl Serialize for PresentationBundle {
+  fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+    serializer.collect_seq(self.presentations.iter().map(SdJwt::presentation))
+  }
+}
+
+impl<'de> Deserialize<'de> for PresentationBundle {
+  fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+    let presentations = Vec::<String>::deserialize(deserializer)?
+      .iter()
+      .map(|presentation| SdJwt::parse(presentation))
+      .collect::<crate::Result<Vec<_>>>()
+      .map_err(serde::de::Error::custom)?;
+    Ok(Self { presentations })
+  }
+}
+
+#[cfg(all(test, feature = "sha"))]
+mod test {
+  use super::PresentationBundle;
+  use crate::test_utils;
+  use crate::Hasher;
+  use crate::HasherRegistry;
+  use crate::KeyBindingJwtClaims;
+  use crate::SdJwt;
+  use crate::SdObjectEncoder;
+  use crate::Sha256Hasher;
+  use crate::ValidationOptions;
+  use serde_json::json;
+
+  fn presentation(name: &str, nonce: &str) -> SdJwt {
+    presentation_with_hasher(name, nonce, Sha256Hasher::new())
+  }
+
+  fn presentation_with_hasher<H: Hasher + Clone + 'static>(name: &str, nonce: &str, hasher: H) -> SdJwt {
+    let object = json!({ "name": name, "cnf": { "kid": "holder" } });
+    let mut encoder = SdObjectEncoder::with_custom_hasher(&object.to_string(), hasher.clone()).unwrap();
+    encoder.add_sd_alg_property();
+    let disclosure = encoder.conceal("/name", None).unwrap().into_string();
+    let issuer_jwt = test_utils::jwt(json!({ "alg": "ES256" }), encoder.try_to_string().unwrap());
+    let claims = KeyBindingJwtClaims::new(
+      &hasher,
+      issuer_jwt.clone(),
+      vec![disclosure.clone()],
+      nonce.to_string(),
+      "https://verifier.example.org".to_string(),
+      0,
+    );
+    let kb_jwt = test_utils::jwt(
+      json!({ "alg": "ES256", "typ": "kb+jwt" }),
+      serde_json::to_string(&claims).unwrap(),
+    );
+    SdJwt::new(issuer_jwt, vec![disclosure], Some(kb_jwt))
+  }
+
+  #[test]
+  fn bundle() {
+    let hashers = HasherRegistry::with_sha256();
+    let options = ValidationOptions {
+      audience: Some("https://verifier.example.org".to_string()),
+      nonce: Some("nonce".to_string()),
+      ..Default::default()
+    };
+    let bundle = PresentationBundle::new(vec![presentation("pid", "nonce"), presentation("mdl", "nonce")]);
+    assert!(bundle.is_valid(&hashers, &options));
+
+    let serialized = serde_json::to_value(&bundle).unwrap();
+    assert_eq!(serialized.as_array().unwrap().len(), 2);
+    let deserialized: PresentationBundle = serde_json::from_value(serialized).unwrap();
+    assert_eq!(deserialized, bundle);
+
+    let mut replayed = bundle;
+    replayed.push(presentation("pid", "other nonce"));
+    assert!(!replayed.is_valid(&hashers, &options));
+    assert!(!PresentationBundle::default().is_valid(&hashers, &options));
+  }
+
+  #[test]
+  fn bundle_without_nonce() {
+    let hashers = HasherRegistry::with_sha256();
+    let options = ValidationOptions::default();
+    let bundle = PresentationBundle::new(vec![presentation("pid", "nonce"), presentation("mdl", "nonce")]);
+    assert!(bundle.is_valid(&hashers, &options));
+
+    let mut mixed = bundle;
+    mixed.push(presentation("pid", "other nonce"));
+    let reports = mixed.validate(&hashers, &options);
+    assert!(reports[1].is_valid());
+    assert_eq!(
+      reports[2].errors().next().unwrap().message,
+      "nonce of the key binding JWT differs from the first presentation"
+    );
+    assert!(!mixed.is_valid(&hashers, &options));
+  }
+
+  #[cfg(feature = "sha-512")]
+  #[test]
+  fn bundle_with_mixed_hashers() {
+    let options = ValidationOptions::default();
+    let bundle = PresentationBundle::new(vec![
+      presentation("pid", "nonce"),
+      presentation_with_hasher("mdl", "nonce", crate::Sha512Hasher::new()),
+    ]);
+    assert!(bundle.is_valid(&HasherRegistry::with_available_hashers(), &options));
+
+    let reports = bundle.validate(&HasherRegistry::with_sha256(), &options);
+    assert!(reports[0].is_valid());
+    assert_eq!(
+      reports[1].errors().next().unwrap().message,
+      crate::Error::MissingHasher("sha-512".to_owned()).to_string()
+    );
+  }
+}
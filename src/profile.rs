@@ -0,0 +1,145 @@
+// Copyright 2020-2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use serde_json::Map;
+use serde_json::Value;
+
+use crate::ClaimViolation;
+use crate::Hasher;
+use crate::SdJwt;
+use crate::Severity;
+use crate::Typ;
+use crate::ValidationCheck;
+use crate::ValidationOptions;
+use crate::ValidationReport;
+
+/// A set of requirements of an interoperability profile, checked on top of the SD-JWT specification at
+/// issuance with [`Profile::validate_issuance`] and at verification with [`Profile::validate`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Profile {
+  /// The [OpenID4VC High Assurance Interoperability Profile][haip] for SD-JWT VCs: `ES256` signatures,
+  /// `typ` `dc+sd-jwt`, a holder key in `cnf` with mandatory key binding and a `status` claim for revocation.
+  ///
+  /// [haip]: https://openid.net/specs/openid4vc-high-assurance-interoperability-profile-1_0.html
+  Haip,
+}
+
+impl Profile {
+  /// Returns the algorithms accepted for the issuer-signed and key binding JWT.
+  pub fn allowed_algs(&self) -> &'static [&'static str] {
+    match self {
+      Self::Haip => &["ES256"],
+    }
+  }
+
+  /// Returns the accepted `typ` headers of the issuer-signed JWT.
+  pub fn allowed_typs(&self) -> &'static [Typ] {
+    match self {
+      Self::Haip => &[Typ::DcSdJwt, Typ::VcSdJwt],
+    }
+  }
+
+  /// Returns [`ValidationOptions`] enforcing the algorithms and key binding of the profile at `now`.
+  pub fn validation_options(&self, now: i64) -> ValidationOptions {
+    ValidationOptions {
+      now,
+      allowed_algs: self.allowed_algs().iter().map(|alg| alg.to_string()).collect(),
+      key_binding_required: true,
+      ..Default::default()
+    }
+  }
+
+  /// Checks the payload `object` of an SD-JWT before it is signed, see [`validate_claims`](crate::validate_claims),
+  /// and additionally requires the claims mandated by the profile.
+  pub fn validate_issuance(&self, object: &Map<String, Value>) -> Vec<ClaimViolation> {
+    let mut violations = crate::validate_claims(object, true);
+    let mut require = |claim: &str, present: bool, reason: &str| {
+      if !present {
+        violations.push(ClaimViolation {
+          path: format!("/{}", claim),
+          reason: reason.to_owned(),
+        });
+      }
+    };
+    match self {
+      Self::Haip => {
+        require(
+          "vct",
+          object.get("vct").map_or(false, Value::is_string),
+          "`vct` string is required",
+        );
+        require(
+          "status",
+          object.get("status").map_or(false, Value::is_object),
+          "`status` object is required",
+        );
+        let jwk = object.get("cnf").and_then(|cnf| cnf.get("jwk"));
+        require(
+          "cnf",
+          jwk.map_or(false, Value::is_object),
+          "holder key must be embedded as `jwk`",
+        );
+      }
+    }
+    violations
+  }
+
+  /// Validates `sd_jwt` with [`SdJwt::validate_full`] using the options of `options` restricted to the
+  /// profile, and reports violations of the profile's additional requirements.
+  pub fn validate(&self, sd_jwt: &SdJwt, hasher: &dyn Hasher, options: &ValidationOptions) -> ValidationReport {
+    let defaults = self.validation_options(options.now);
+    let options = ValidationOptions {
+      allowed_algs: defaults.allowed_algs,
+      key_binding_required: defaults.key_binding_required,
+      ..options.clone()
+    };
+    let mut report = sd_jwt.validate_full(hasher, &options);
+    match self {
+      Self::Haip => {
+        if let Err(error) = sd_jwt.validate_typ(self.allowed_typs()) {
+          report.push(ValidationCheck::Structure, Severity::Error, error.to_string());
+        }
+        let claims = sd_jwt.parse_jwt().map(|jwt| jwt.into_claims()).unwrap_or_default();
+        if !claims.get("status").map_or(false, Value::is_object) {
+          report.push(
+            ValidationCheck::Claims,
+            Severity::Error,
+            "`status` is required to check the revocation status",
+          );
+        }
+      }
+    }
+    report
+  }
+}
+
+#[cfg(all(test, feature = "sha"))]
+mod test {
+  use super::Profile;
+  use crate::test_utils;
+  use crate::SdJwt;
+  use crate::Sha256Hasher;
+  use serde_json::json;
+
+  #[test]
+  fn haip() {
+    let object = json!({
+      "iss": "https://issuer.example.com",
+      "vct": "https://credentials.example.com/identity_credential",
+      "status": { "status_list": { "idx": 0, "uri": "https://issuer.example.com/statuslists/1" } },
+      "cnf": { "jwk": { "kty": "EC", "crv": "P-256", "x": "x", "y": "y" } }
+    });
+    assert!(Profile::Haip.validate_issuance(object.as_object().unwrap()).is_empty());
+    let violations = Profile::Haip.validate_issuance(json!({ "cnf": { "kid": "key-1" } }).as_object().unwrap());
+    let paths: Vec<&str> = violations.iter().map(|violation| violation.path.as_str()).collect();
+    assert_eq!(paths, vec!["/vct", "/status", "/cnf"]);
+
+    let jwt = test_utils::jwt(json!({ "alg": "ES384", "typ": "sd-jwt" }), object.to_string());
+    let sd_jwt = SdJwt::new(jwt, vec![], None);
+    let options = Profile::Haip.validation_options(0);
+    let report = Profile::Haip.validate(&sd_jwt, &Sha256Hasher::new(), &options);
+    // Disallowed algorithm, missing key binding JWT and wrong typ.
+    assert_eq!(report.errors().count(), 3);
+  }
+}
@@ -0,0 +1,242 @@
+// Copyright 2020-2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use serde::Deserialize;
+use serde::Serialize;
+use serde_json::Map;
+use serde_json::Value;
+
+use crate::Error;
+use crate::Hasher;
+use crate::Jwk;
+use crate::Result;
+
+/// The value of the `cnf` claim of an SD-JWT, referencing the key the holder must prove possession of in
+/// the key binding JWT.
+///
+/// The members are defined in [RFC 7800](https://www.rfc-editor.org/rfc/rfc7800.html#section-3.1) and,
+/// for certificates, [RFC 8705](https://www.rfc-editor.org/rfc/rfc8705.html#section-3.1) and
+/// [RFC 7517](https://www.rfc-editor.org/rfc/rfc7517.html#section-4.7).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+#[non_exhaustive]
+pub enum RequiredKeyBinding {
+  /// The holder's public key as a JWK, validated on deserialization, see [`Jwk`].
+  Jwk { jwk: Jwk },
+  /// The holder's public key as an encrypted JWK.
+  Jwe { jwe: String },
+  /// A URL of a JWK Set and the ID of the holder's key in it.
+  Jku {
+    /// Tokens issued by earlier versions use the non-standard `jwu` member.
+    #[serde(alias = "jwu")]
+    jku: String,
+    kid: String,
+  },
+  /// The ID of the holder's key.
+  Kid { kid: String },
+  /// The holder's X.509 certificate chain, as base64-encoded DER certificates.
+  X5c { x5c: Vec<String> },
+  /// The base64url-encoded SHA-256 thumbprint of the holder's X.509 certificate.
+  X5tS256 {
+    #[serde(rename = "x5t#S256")]
+    x5t_s256: String,
+  },
+  /// The [RFC 7638](https://www.rfc-editor.org/rfc/rfc7638.html) thumbprint of the holder's public key,
+  /// as used by [RFC 9449](https://www.rfc-editor.org/rfc/rfc9449.html#section-6.1).
+  Jkt { jkt: String },
+  /// Any other confirmation method.
+  ///
+  /// Members of other variants that failed to deserialize, e.g. a malformed `jwk`, are rejected instead of
+  /// being accepted as custom confirmation method.
+  Custom(#[serde(deserialize_with = "deserialize_custom")] Map<String, Value>),
+}
+
+impl RequiredKeyBinding {
+  /// Returns the embedded key of a [`RequiredKeyBinding::Jwk`], `None` for all other variants.
+  pub fn jwk(&self) -> Option<&Jwk> {
+    match self {
+      Self::Jwk { jwk } => Some(jwk),
+      _ => None,
+    }
+  }
+
+  /// Creates a [`RequiredKeyBinding::Jkt`] referencing `jwk` by its thumbprint computed with `hasher`,
+  /// for issuers that do not embed the holder's key.
+  ///
+  /// ## Error
+  /// Same as [`Jwk::thumbprint`].
+  pub fn thumbprint(jwk: &Jwk, hasher: &dyn Hasher) -> Result<Self> {
+    jwk.thumbprint(hasher).map(|jkt| Self::Jkt { jkt })
+  }
+
+  /// Returns whether `jwk`, the key the key binding JWT was verified with, is the key required by this
+  /// key binding. Embedded keys and thumbprints are compared by their thumbprints computed with `hasher`,
+  /// all other variants return `false`.
+  ///
+  /// ## Error
+  /// Same as [`Jwk::thumbprint`].
+  pub fn matches_jwk(&self, jwk: &Jwk, hasher: &dyn Hasher) -> Result<bool> {
+    let expected = match self {
+      Self::Jwk { jwk: required } => required.thumbprint(hasher)?,
+      Self::Jkt { jkt } => jkt.clone(),
+      _ => return Ok(false),
+    };
+    Ok(crate::digests_equal(&expected, &jwk.thumbprint(hasher)?))
+  }
+
+  /// Looks up the referenced key in `jwk_set`, a JWK Set as defined in
+  /// [RFC 7517](https://www.rfc-editor.org/rfc/rfc7517.html#section-5), by its `kid`.
+  ///
+  /// For [`RequiredKeyBinding::Jku`] the caller is expected to have retrieved `jwk_set` from `jku`. For
+  /// [`RequiredKeyBinding::Jwk`] the embedded key is returned and `jwk_set` is ignored. Returns `None` for
+  /// all variants without key ID or if no key matches.
+  ///
+  /// ## Error
+  /// Returns [`Error::DeserializationError`] if the matching key is not a valid JWK.
+  pub fn resolve_jwk(&self, jwk_set: &Map<String, Value>) -> Result<Option<Jwk>> {
+    let kid = match self {
+      Self::Jwk { jwk } => return Ok(Some(jwk.clone())),
+      Self::Jku { kid, .. } | Self::Kid { kid } => kid,
+      _ => return Ok(None),
+    };
+    let keys = jwk_set
+      .get("keys")
+      .and_then(Value::as_array)
+      .ok_or_else(|| Error::DeserializationError("JWK set has no `keys` array".to_string()))?;
+    keys
+      .iter()
+      .filter_map(Value::as_object)
+      .find(|key| key.get("kid").and_then(Value::as_str) == Some(kid.as_str()))
+      .map(|key| Jwk::try_from(key.clone()))
+      .transpose()
+  }
+}
+
+/// Members identifying one of the dedicated variants of [`RequiredKeyBinding`].
+const KNOWN_MEMBERS: [&str; 7] = ["jwk", "jwe", "jku", "jwu", "x5c", "x5t#S256", "jkt"];
+
+fn deserialize_custom<'de, D>(deserializer: D) -> std::result::Result<Map<String, Value>, D::Error>
+where
+  D: serde::Deserializer<'de>,
+{
+  let members = Map::<String, Value>::deserialize(deserializer)?;
+  match KNOWN_MEMBERS.iter().find(|member| members.contains_key(**member)) {
+    Some(member) => Err(serde::de::Error::custom(format!(
+      "invalid `{}` confirmation method",
+      member
+    ))),
+    None => Ok(members),
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::RequiredKeyBinding;
+  use serde_json::json;
+
+  #[test]
+  fn serde_members() {
+    let jwk = json!({
+      "jwk": {
+        "kty": "EC",
+        "crv": "P-256",
+        "x": "TCAER19Zvu3OHF4j4W4vfSVoHIP1ILilDls7vCeGemc",
+        "y": "ZxjiWWbZMQGHVWKVQ4hbSIirsVfuecCE6t4jT9F2HZQ"
+      }
+    });
+    let key_binding: RequiredKeyBinding = serde_json::from_value(jwk.clone()).unwrap();
+    assert!(matches!(key_binding, RequiredKeyBinding::Jwk { .. }));
+    assert_eq!(serde_json::to_value(&key_binding).unwrap(), jwk);
+
+    let jku = json!({ "jku": "https://holder.example.com/jwks.json", "kid": "key-1" });
+    let key_binding: RequiredKeyBinding = serde_json::from_value(jku.clone()).unwrap();
+    assert!(matches!(key_binding, RequiredKeyBinding::Jku { .. }));
+    assert_eq!(serde_json::to_value(&key_binding).unwrap(), jku);
+
+    let x5t = json!({ "x5t#S256": "bwcK0esc3ACC3DB2Y5_lESsXE8o9ltc05O89jdN-dg2" });
+    let key_binding: RequiredKeyBinding = serde_json::from_value(x5t.clone()).unwrap();
+    assert!(matches!(key_binding, RequiredKeyBinding::X5tS256 { .. }));
+    assert_eq!(serde_json::to_value(&key_binding).unwrap(), x5t);
+
+    let key_binding: RequiredKeyBinding = serde_json::from_value(json!({ "kid": "key-1" })).unwrap();
+    assert_eq!(
+      key_binding,
+      RequiredKeyBinding::Kid {
+        kid: "key-1".to_string()
+      }
+    );
+
+    let key_binding: RequiredKeyBinding = serde_json::from_value(json!({ "osc": "holder" })).unwrap();
+    assert!(matches!(key_binding, RequiredKeyBinding::Custom(_)));
+  }
+
+  #[test]
+  fn malformed_jwk() {
+    assert!(serde_json::from_value::<RequiredKeyBinding>(json!({ "jwk": { "kty": "EC", "crv": "P-256" } })).is_err());
+    assert!(serde_json::from_value::<RequiredKeyBinding>(json!({ "jwk": "key" })).is_err());
+    assert!(serde_json::from_value::<RequiredKeyBinding>(json!({ "jku": "https://holder.example.com" })).is_err());
+  }
+
+  #[test]
+  fn legacy_jwu() {
+    let key_binding: RequiredKeyBinding =
+      serde_json::from_value(json!({ "jwu": "https://holder.example.com/jwks.json", "kid": "key-1" })).unwrap();
+    assert_eq!(
+      serde_json::to_value(&key_binding).unwrap(),
+      json!({ "jku": "https://holder.example.com/jwks.json", "kid": "key-1" })
+    );
+  }
+
+  #[test]
+  fn resolve_jwk() {
+    let jwk_set = json!({
+      "keys": [
+        { "kty": "OKP", "crv": "Ed25519", "kid": "key-0", "x": "11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo" },
+        { "kty": "EC", "crv": "P-256", "kid": "key-1", "x": "TCAER19Zvu3OHF4j4W4vfSVoHIP1ILilDls7vCeGemc", "y": "ZxjiWWbZMQGHVWKVQ4hbSIirsVfuecCE6t4jT9F2HZQ" }
+      ]
+    });
+    let jwk_set = jwk_set.as_object().unwrap();
+    let key_binding: RequiredKeyBinding =
+      serde_json::from_value(json!({ "jku": "https://holder.example.com/jwks.json", "kid": "key-1" })).unwrap();
+    assert_eq!(key_binding.jwk(), None);
+    let jwk = key_binding.resolve_jwk(jwk_set).unwrap().unwrap();
+    assert_eq!(jwk.crv(), Some("P-256"));
+
+    let key_binding: RequiredKeyBinding = serde_json::from_value(json!({ "kid": "key-2" })).unwrap();
+    assert_eq!(key_binding.resolve_jwk(jwk_set).unwrap(), None);
+
+    let key_binding: RequiredKeyBinding = serde_json::from_value(json!({ "jwk": jwk_set["keys"][0] })).unwrap();
+    assert_eq!(key_binding.jwk().unwrap().kty(), "OKP");
+  }
+
+  #[cfg(feature = "sha")]
+  #[test]
+  fn matches_jwk() {
+    let hasher = crate::Sha256Hasher::new();
+    let holder_key = json!({
+      "kty": "EC",
+      "crv": "P-256",
+      "x": "TCAER19Zvu3OHF4j4W4vfSVoHIP1ILilDls7vCeGemc",
+      "y": "ZxjiWWbZMQGHVWKVQ4hbSIirsVfuecCE6t4jT9F2HZQ"
+    });
+    let jwk: crate::Jwk = serde_json::from_value(holder_key.clone()).unwrap();
+    let other: crate::Jwk = serde_json::from_value(
+      json!({ "kty": "OKP", "crv": "Ed25519", "x": "11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo" }),
+    )
+    .unwrap();
+
+    let thumbprint = RequiredKeyBinding::thumbprint(&jwk, &hasher).unwrap();
+    let serialized = serde_json::to_value(&thumbprint).unwrap();
+    assert!(serialized.get("jkt").is_some());
+    assert_eq!(
+      serde_json::from_value::<RequiredKeyBinding>(serialized).unwrap(),
+      thumbprint
+    );
+    assert!(thumbprint.matches_jwk(&jwk, &hasher).unwrap());
+    assert!(!thumbprint.matches_jwk(&other, &hasher).unwrap());
+
+    let embedded: RequiredKeyBinding = serde_json::from_value(json!({ "jwk": holder_key })).unwrap();
+    assert!(embedded.matches_jwk(&jwk, &hasher).unwrap());
+    assert!(!embedded.matches_jwk(&other, &hasher).unwrap());
+  }
+}
@@ -0,0 +1,153 @@
+// Copyright 2020-2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use serde_json::Map;
+use serde_json::Value;
+
+use crate::pointer::join_pointer;
+use crate::Error;
+use crate::Result;
+use crate::SdJwt;
+use crate::Typ;
+use crate::SD_ALG;
+
+/// The draft of the SD-JWT specification a token is issued or verified under.
+///
+/// The default is the latest supported draft. Older drafts are meant for tokens of deployments that
+/// have not been upgraded yet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[non_exhaustive]
+pub enum SpecVersion {
+  /// [Draft 07](https://www.ietf.org/archive/id/draft-ietf-oauth-selective-disclosure-jwt-07.html), in
+  /// which the key binding JWT carries the digest of the SD-JWT in the `_sd_hash` claim.
+  Draft07,
+  /// [Draft 08](https://www.ietf.org/archive/id/draft-ietf-oauth-selective-disclosure-jwt-08.html) and
+  /// later, in which the claim was renamed to `sd_hash`.
+  #[default]
+  Draft08,
+}
+
+impl SpecVersion {
+  /// Returns the name of the key binding JWT claim holding the digest of the SD-JWT.
+  pub fn sd_hash_claim(&self) -> &'static str {
+    match self {
+      Self::Draft07 => "_sd_hash",
+      Self::Draft08 => "sd_hash",
+    }
+  }
+
+  /// Returns the `typ` headers accepted for the issuer-signed JWT, if it has one. `dc+sd-jwt` was introduced
+  /// after draft 07.
+  pub fn allowed_typs(&self) -> &'static [Typ] {
+    match self {
+      Self::Draft07 => &[Typ::SdJwt, Typ::VcSdJwt],
+      Self::Draft08 => &[Typ::SdJwt, Typ::VcSdJwt, Typ::DcSdJwt],
+    }
+  }
+
+  /// Returns whether `_sd_alg` is rejected below the top level of the payload. Nested `_sd_alg` claims, as
+  /// emitted by some issuers of draft 07 tokens, are tolerated and ignored for [`SpecVersion::Draft07`].
+  pub fn strict_sd_alg_placement(&self) -> bool {
+    match self {
+      Self::Draft07 => false,
+      Self::Draft08 => true,
+    }
+  }
+}
+
+impl SdJwt {
+  /// Checks the issuer-signed JWT against the rules of `version` that differ between drafts: the `typ`
+  /// header, if present, must be one of [`SpecVersion::allowed_typs`] and `_sd_alg` must be placed as
+  /// required by [`SpecVersion::strict_sd_alg_placement`].
+  ///
+  /// The claim name of the digest in the key binding JWT is checked by
+  /// [`SdJwt::validate_full`](crate::SdJwt::validate_full) with [`SpecVersion::sd_hash_claim`].
+  ///
+  /// ## Error
+  /// * [`Error::UnexpectedTyp`] if `typ` is not accepted by `version`.
+  /// * [`Error::DataTypeMismatch`] if `_sd_alg` is misplaced.
+  /// * Same as [`SdJwt::parse_jwt`].
+  pub fn validate_spec_version(&self, version: SpecVersion) -> Result<()> {
+    let jwt = self.parse_jwt()?;
+    if let Some(typ) = jwt.typ() {
+      let allowed = version.allowed_typs();
+      if !typ.parse::<Typ>().map_or(false, |typ| allowed.contains(&typ)) {
+        return Err(Error::UnexpectedTyp {
+          expected: allowed.iter().map(Typ::as_str).collect::<Vec<_>>().join(" or "),
+          found: Some(typ.to_owned()),
+        });
+      }
+    }
+    if version.strict_sd_alg_placement() {
+      for (key, value) in jwt.claims() {
+        if let Some(path) = nested_sd_alg(value, &join_pointer("", key)) {
+          return Err(Error::DataTypeMismatch {
+            path,
+            reason: "`_sd_alg` is only allowed at the top level".to_string(),
+          });
+        }
+      }
+    }
+    Ok(())
+  }
+}
+
+/// Returns the path of the first `_sd_alg` claim in `value` at `path`.
+fn nested_sd_alg(value: &Value, path: &str) -> Option<String> {
+  match value {
+    Value::Object(object) => nested_sd_alg_in_object(object, path),
+    Value::Array(array) => array
+      .iter()
+      .enumerate()
+      .find_map(|(index, element)| nested_sd_alg(element, &join_pointer(path, &index.to_string()))),
+    _ => None,
+  }
+}
+
+fn nested_sd_alg_in_object(object: &Map<String, Value>, path: &str) -> Option<String> {
+  object.iter().find_map(|(key, value)| {
+    let path = join_pointer(path, key);
+    if key == SD_ALG {
+      Some(path)
+    } else {
+      nested_sd_alg(value, &path)
+    }
+  })
+}
+
+#[cfg(test)]
+mod test {
+  use super::SpecVersion;
+  use crate::test_utils;
+  use crate::Error;
+  use crate::SdJwt;
+  use serde_json::json;
+
+  #[test]
+  fn validate_spec_version() {
+    let sd_jwt = |typ: &str, claims: serde_json::Value| {
+      SdJwt::new(
+        test_utils::jwt(json!({ "alg": "ES256", "typ": typ }), claims.to_string()),
+        vec![],
+        None,
+      )
+    };
+
+    let dc = sd_jwt("dc+sd-jwt", json!({ "_sd_alg": "sha-256" }));
+    assert!(dc.validate_spec_version(SpecVersion::Draft08).is_ok());
+    assert!(matches!(
+      dc.validate_spec_version(SpecVersion::Draft07).unwrap_err(),
+      Error::UnexpectedTyp { .. }
+    ));
+    assert!(sd_jwt("jwt", json!({}))
+      .validate_spec_version(SpecVersion::Draft08)
+      .is_err());
+
+    let nested = sd_jwt("vc+sd-jwt", json!({ "address": [{ "_sd_alg": "sha-256" }] }));
+    assert!(nested.validate_spec_version(SpecVersion::Draft07).is_ok());
+    assert!(matches!(
+      nested.validate_spec_version(SpecVersion::Draft08).unwrap_err(),
+      Error::DataTypeMismatch { path, .. } if path == "/address/0/_sd_alg"
+    ));
+  }
+}
@@ -0,0 +1,211 @@
+// Copyright 2020-2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use serde::Deserialize;
+use serde::Serialize;
+use serde_json::Map;
+use serde_json::Value;
+
+use crate::decoder::sd_alg;
+use crate::ClaimEntry;
+use crate::DecodingContext;
+use crate::Error;
+use crate::Result;
+use crate::SdJwt;
+
+/// Envelope for persisting an [`SdJwt`] in a wallet together with holder metadata.
+///
+/// The envelope (de)serializes with a `version` member, deserialization fails for versions other than
+/// [`StoredSdJwt::VERSION`].
+///
+/// ## Note
+///
+/// Unless the `debug-plaintext` feature is enabled, the [`Debug`](std::fmt::Debug) output redacts the token
+/// and the disclosures.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "debug-plaintext", derive(Debug))]
+#[serde(into = "StoredSdJwtRepr", try_from = "StoredSdJwtRepr")]
+pub struct StoredSdJwt {
+  /// The SD-JWT as received from the issuer or as last presented.
+  pub sd_jwt: SdJwt,
+  /// All disclosures received from the issuer, including those removed from `sd_jwt` since.
+  pub disclosures: Vec<String>,
+  /// The hash algorithm of the digests, the value of `_sd_alg` or its default.
+  pub sd_alg: String,
+  /// Information about the issuance, e.g. the credential offer or the time the SD-JWT was received.
+  pub issuance_context: Map<String, Value>,
+  /// User-defined tags.
+  pub tags: Vec<String>,
+}
+
+impl StoredSdJwt {
+  /// The version of the serialization format.
+  pub const VERSION: u32 = 1;
+
+  /// Creates a new [`StoredSdJwt`] for an SD-JWT as received from the issuer, without context and tags.
+  ///
+  /// ## Error
+  /// Returns [`Error::DeserializationError`] or [`Error::DataTypeMismatch`] if the JWT or its `_sd_alg`
+  /// claim can not be parsed.
+  pub fn new(sd_jwt: SdJwt) -> Result<Self> {
+    let claims = sd_jwt.parse_jwt()?.into_claims();
+    let sd_alg = sd_alg(&claims)?.to_owned();
+    Ok(Self {
+      disclosures: sd_jwt.disclosures.clone(),
+      sd_jwt,
+      sd_alg,
+      issuance_context: Map::new(),
+      tags: vec![],
+    })
+  }
+
+  /// Returns every value of the decoded object like [`SdJwt::iter_claims`], including the values revealed
+  /// by [`StoredSdJwt::disclosures`] that are not attached to [`StoredSdJwt::sd_jwt`] as
+  /// [`ClaimState::Concealed`](crate::ClaimState::Concealed).
+  ///
+  /// ## Error
+  /// Same as [`SdJwt::validate_structure`].
+  pub fn iter_claims<'a>(&self, hasher: impl Into<DecodingContext<'a>>) -> Result<std::vec::IntoIter<ClaimEntry>> {
+    self.sd_jwt.claim_entries(hasher, &self.disclosures)
+  }
+
+  /// Returns the SD-JWT with all disclosures received from the issuer and without key binding JWT.
+  pub fn original(&self) -> SdJwt {
+    SdJwt::new(self.sd_jwt.jwt.clone(), self.disclosures.clone(), None)
+  }
+}
+
+#[cfg(not(feature = "debug-plaintext"))]
+impl std::fmt::Debug for StoredSdJwt {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("StoredSdJwt")
+      .field("sd_jwt", &self.sd_jwt)
+      .field(
+        "disclosures",
+        &vec![crate::disclosure::Redacted; self.disclosures.len()],
+      )
+      .field("sd_alg", &self.sd_alg)
+      .field("issuance_context", &self.issuance_context)
+      .field("tags", &self.tags)
+      .finish()
+  }
+}
+
+/// Serialized form of [`StoredSdJwt`].
+#[derive(Serialize, Deserialize)]
+struct StoredSdJwtRepr {
+  version: u32,
+  sd_jwt: String,
+  disclosures: Vec<String>,
+  sd_alg: String,
+  #[serde(default)]
+  issuance_context: Map<String, Value>,
+  #[serde(default)]
+  tags: Vec<String>,
+}
+
+impl From<StoredSdJwt> for StoredSdJwtRepr {
+  fn from(stored: StoredSdJwt) -> Self {
+    Self {
+      version: StoredSdJwt::VERSION,
+      sd_jwt: stored.sd_jwt.presentation(),
+      disclosures: stored.disclosures,
+      sd_alg: stored.sd_alg,
+      issuance_context: stored.issuance_context,
+      tags: stored.tags,
+    }
+  }
+}
+
+impl TryFrom<StoredSdJwtRepr> for StoredSdJwt {
+  type Error = Error;
+
+  fn try_from(repr: StoredSdJwtRepr) -> Result<Self> {
+    if repr.version != Self::VERSION {
+      return Err(Error::DeserializationError(format!(
+        "unsupported stored SD-JWT version {}",
+        repr.version
+      )));
+    }
+    Ok(Self {
+      sd_jwt: SdJwt::parse(&repr.sd_jwt)?,
+      disclosures: repr.disclosures,
+      sd_alg: repr.sd_alg,
+      issuance_context: repr.issuance_context,
+      tags: repr.tags,
+    })
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::StoredSdJwt;
+  use crate::test_utils;
+  use crate::ClaimState;
+  use crate::SdJwt;
+  use serde_json::json;
+
+  // `{"alg": "ES256"}` and `{"_sd_alg": "sha-256"}`.
+  const JWT: &str = "eyJhbGciOiAiRVMyNTYifQ.eyJfc2RfYWxnIjogInNoYS0yNTYifQ.c2ln";
+  const DISCLOSURE: &str = "WyJsa2x4RjVqTVlsR1RQVW92TU5JdkNBIiwgIlVTIl0";
+  const GIVEN_NAME: &str = "WyIyR0xDNDJzS1F2ZUNmR2ZyeU5STjl3IiwgImdpdmVuX25hbWUiLCAiRXJpa2EiXQ";
+
+  #[test]
+  fn round_trip() {
+    let sd_jwt = SdJwt::new(
+      JWT.to_string(),
+      vec![GIVEN_NAME.to_string(), DISCLOSURE.to_string()],
+      None,
+    );
+    let mut stored = StoredSdJwt::new(sd_jwt).unwrap();
+    assert_eq!(stored.sd_alg, "sha-256");
+    stored.tags.push("pid".to_string());
+    stored.sd_jwt.disclosures.truncate(1);
+
+    let serialized = serde_json::to_value(&stored).unwrap();
+    assert_eq!(serialized["version"], json!(1));
+    assert_eq!(serialized["sd_jwt"], json!(format!("{}~{}~", JWT, GIVEN_NAME)));
+    let deserialized: StoredSdJwt = serde_json::from_value(serialized).unwrap();
+    assert_eq!(deserialized, stored);
+    assert_eq!(
+      deserialized.original().disclosures,
+      vec![GIVEN_NAME.to_string(), DISCLOSURE.to_string()]
+    );
+  }
+
+  #[test]
+  fn unsupported_version() {
+    let serialized = json!({
+      "version": 2,
+      "sd_jwt": format!("{}~", JWT),
+      "disclosures": [],
+      "sd_alg": "sha-256"
+    });
+    assert!(serde_json::from_value::<StoredSdJwt>(serialized).is_err());
+  }
+
+  #[cfg(feature = "sha")]
+  #[test]
+  fn iter_claims() {
+    let mut encoder = crate::SdObjectEncoder::try_from(json!({ "given_name": "John", "family_name": "Doe" })).unwrap();
+    let given_name = encoder.conceal("/given_name", None).unwrap().into_string();
+    let family_name = encoder.conceal("/family_name", None).unwrap().into_string();
+    let jwt = test_utils::jwt(json!({ "alg": "ES256" }), encoder.try_to_string().unwrap());
+
+    let mut stored = StoredSdJwt::new(SdJwt::new(jwt, vec![given_name, family_name], None)).unwrap();
+    stored.sd_jwt.disclosures.truncate(1);
+    let mut entries: Vec<(String, ClaimState)> = stored
+      .iter_claims(&crate::Sha256Hasher::new())
+      .unwrap()
+      .map(|entry| (entry.path, entry.state))
+      .collect();
+    entries.sort_by(|a, b| a.0.cmp(&b.0));
+    assert_eq!(
+      entries,
+      vec![
+        ("/family_name".to_owned(), ClaimState::Concealed),
+        ("/given_name".to_owned(), ClaimState::Disclosed)
+      ]
+    );
+  }
+}
@@ -0,0 +1,15 @@
+// Copyright 2020-2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Fixtures shared by the unit tests.
+
+use serde_json::Value;
+
+/// Returns a JWT of `header` and `claims` with the dummy signature `sig`.
+pub(crate) fn jwt(header: Value, claims: impl AsRef<[u8]>) -> String {
+  format!(
+    "{}.{}.c2ln",
+    multibase::Base::Base64Url.encode(header.to_string()),
+    multibase::Base::Base64Url.encode(claims)
+  )
+}
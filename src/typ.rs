@@ -0,0 +1,73 @@
+// Copyright 2020-2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::fmt::Display;
+use std::str::FromStr;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::Error;
+
+/// The `typ` header value of an issuer-signed JWT, identifying the profile of the SD-JWT.
+///
+/// Issuers set [`Typ::as_str`] as `typ` in the header they sign, verifiers check it with
+/// [`SdJwt::validate_typ`](crate::SdJwt::validate_typ).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[non_exhaustive]
+pub enum Typ {
+  /// Plain SD-JWT, see [`HEADER_TYP`](crate::HEADER_TYP).
+  #[serde(rename = "sd-jwt")]
+  SdJwt,
+  /// SD-JWT VC as used by earlier drafts of SD-JWT-based Verifiable Credentials.
+  #[serde(rename = "vc+sd-jwt")]
+  VcSdJwt,
+  /// SD-JWT VC as used by current drafts of SD-JWT-based Verifiable Credentials.
+  #[serde(rename = "dc+sd-jwt")]
+  DcSdJwt,
+}
+
+impl Typ {
+  /// Returns the header value, e.g. `dc+sd-jwt`.
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      Self::SdJwt => crate::HEADER_TYP,
+      Self::VcSdJwt => "vc+sd-jwt",
+      Self::DcSdJwt => "dc+sd-jwt",
+    }
+  }
+}
+
+impl Display for Typ {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.write_str(self.as_str())
+  }
+}
+
+impl FromStr for Typ {
+  type Err = Error;
+
+  /// Parses a header value, ignoring ASCII case as media types are case-insensitive.
+  fn from_str(typ: &str) -> Result<Self, Self::Err> {
+    [Self::SdJwt, Self::VcSdJwt, Self::DcSdJwt]
+      .into_iter()
+      .find(|candidate| candidate.as_str().eq_ignore_ascii_case(typ))
+      .ok_or_else(|| Error::DeserializationError(format!("unknown SD-JWT typ {}", typ)))
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::Typ;
+  use serde_json::json;
+
+  #[test]
+  fn from_str_and_serde() {
+    assert_eq!("dc+sd-jwt".parse::<Typ>().unwrap(), Typ::DcSdJwt);
+    assert_eq!("VC+SD-JWT".parse::<Typ>().unwrap(), Typ::VcSdJwt);
+    assert!("jwt".parse::<Typ>().is_err());
+    assert_eq!(Typ::SdJwt.to_string(), "sd-jwt");
+    assert_eq!(serde_json::to_value(Typ::DcSdJwt).unwrap(), json!("dc+sd-jwt"));
+    assert_eq!(serde_json::from_value::<Typ>(json!("vc+sd-jwt")).unwrap(), Typ::VcSdJwt);
+  }
+}
@@ -0,0 +1,232 @@
+// Copyright 2020-2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::cell::RefCell;
+
+use serde::de::DeserializeOwned;
+use serde::de::DeserializeSeed;
+use serde::de::IntoDeserializer;
+use serde::de::MapAccess;
+use serde::de::SeqAccess;
+use serde::de::Visitor;
+use serde::Deserializer;
+use serde_json::Map;
+use serde_json::Value;
+
+use crate::pointer::join_pointer;
+use crate::DecodingContext;
+use crate::Error;
+use crate::Result;
+use crate::SdJwt;
+
+impl SdJwt {
+  /// Resolves the disclosures of this SD-JWT with `hasher` and deserializes the disclosed object into `T`.
+  ///
+  /// ## Warning
+  /// The signature of the JWT is not verified.
+  ///
+  /// ## Error
+  /// * [`Error::DataTypeMismatch`] with the JSON pointer to the offending claim if the disclosed object can
+  ///   not be deserialized into `T`.
+  /// * Same as [`SdJwt::disclosed_object`].
+  pub fn into_typed_object<'a, T: DeserializeOwned>(self, hasher: impl Into<DecodingContext<'a>>) -> Result<T> {
+    deserialize_object(self.disclosed_object(hasher)?)
+  }
+}
+
+/// Deserializes `object` into `T`, reporting the path of the value that failed to deserialize.
+fn deserialize_object<T: DeserializeOwned>(object: Map<String, Value>) -> Result<T> {
+  let failed_path = RefCell::new(None);
+  let deserializer = Tracked {
+    value: Value::Object(object),
+    path: String::new(),
+    failed_path: &failed_path,
+  };
+  T::deserialize(deserializer).map_err(|err| Error::DataTypeMismatch {
+    path: failed_path.into_inner().unwrap_or_default(),
+    reason: err.to_string(),
+  })
+}
+
+/// A deserializer over `value` at `path` that records the innermost path at which deserialization fails.
+struct Tracked<'a> {
+  value: Value,
+  path: String,
+  failed_path: &'a RefCell<Option<String>>,
+}
+
+impl<'a> Tracked<'a> {
+  fn child(&self, value: Value, segment: &str) -> Self {
+    Self {
+      value,
+      path: join_pointer(&self.path, segment),
+      failed_path: self.failed_path,
+    }
+  }
+
+  /// Takes the value out of this deserializer, leaving one that only tracks the path.
+  fn split(self) -> (Value, Self) {
+    let Self {
+      value,
+      path,
+      failed_path,
+    } = self;
+    (
+      value,
+      Self {
+        value: Value::Null,
+        path,
+        failed_path,
+      },
+    )
+  }
+
+  fn record<T>(&self, result: serde_json::Result<T>) -> serde_json::Result<T> {
+    if result.is_err() {
+      self.failed_path.borrow_mut().get_or_insert_with(|| self.path.clone());
+    }
+    result
+  }
+}
+
+impl<'de, 'a> Deserializer<'de> for Tracked<'a> {
+  type Error = serde_json::Error;
+
+  fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> serde_json::Result<V::Value> {
+    let (value, parent) = self.split();
+    let result = match value {
+      Value::Object(object) => visitor.visit_map(TrackedMap {
+        entries: object.into_iter(),
+        value: None,
+        parent: &parent,
+      }),
+      Value::Array(array) => visitor.visit_seq(TrackedSeq {
+        elements: array.into_iter().enumerate(),
+        parent: &parent,
+      }),
+      value => value.deserialize_any(visitor),
+    };
+    parent.record(result)
+  }
+
+  fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> serde_json::Result<V::Value> {
+    match self.value {
+      Value::Null => visitor.visit_none(),
+      _ => visitor.visit_some(self),
+    }
+  }
+
+  fn deserialize_newtype_struct<V: Visitor<'de>>(
+    self,
+    _name: &'static str,
+    visitor: V,
+  ) -> serde_json::Result<V::Value> {
+    visitor.visit_newtype_struct(self)
+  }
+
+  fn deserialize_enum<V: Visitor<'de>>(
+    self,
+    name: &'static str,
+    variants: &'static [&'static str],
+    visitor: V,
+  ) -> serde_json::Result<V::Value> {
+    let (value, parent) = self.split();
+    parent.record(value.deserialize_enum(name, variants, visitor))
+  }
+
+  serde::forward_to_deserialize_any! {
+    bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf unit unit_struct seq
+    tuple tuple_struct map struct identifier ignored_any
+  }
+}
+
+struct TrackedMap<'a, 'b> {
+  entries: serde_json::map::IntoIter,
+  value: Option<(String, Value)>,
+  parent: &'b Tracked<'a>,
+}
+
+impl<'de, 'a, 'b> MapAccess<'de> for TrackedMap<'a, 'b> {
+  type Error = serde_json::Error;
+
+  fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> serde_json::Result<Option<K::Value>> {
+    match self.entries.next() {
+      Some((key, value)) => {
+        let deserialized = seed.deserialize(key.clone().into_deserializer()).map(Some);
+        self.value = Some((key, value));
+        deserialized
+      }
+      None => Ok(None),
+    }
+  }
+
+  fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> serde_json::Result<V::Value> {
+    let (key, value) = self
+      .value
+      .take()
+      .ok_or_else(|| serde::de::Error::custom("value is missing"))?;
+    seed.deserialize(self.parent.child(value, &key))
+  }
+}
+
+struct TrackedSeq<'a, 'b> {
+  elements: std::iter::Enumerate<std::vec::IntoIter<Value>>,
+  parent: &'b Tracked<'a>,
+}
+
+impl<'de, 'a, 'b> SeqAccess<'de> for TrackedSeq<'a, 'b> {
+  type Error = serde_json::Error;
+
+  fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> serde_json::Result<Option<T::Value>> {
+    match self.elements.next() {
+      Some((index, element)) => seed
+        .deserialize(self.parent.child(element, &index.to_string()))
+        .map(Some),
+      None => Ok(None),
+    }
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::deserialize_object;
+  use crate::Error;
+  use serde::Deserialize;
+  use serde_json::json;
+
+  #[derive(Debug, Deserialize, PartialEq)]
+  struct Address {
+    country: String,
+    zip: Option<u32>,
+  }
+
+  #[derive(Debug, Deserialize, PartialEq)]
+  struct Credential {
+    given_name: String,
+    addresses: Vec<Address>,
+  }
+
+  fn object(value: serde_json::Value) -> serde_json::Map<String, serde_json::Value> {
+    value.as_object().unwrap().clone()
+  }
+
+  #[test]
+  fn typed_object() {
+    let credential: Credential = deserialize_object(object(json!({
+      "given_name": "Erika",
+      "addresses": [{ "country": "DE", "zip": 10115 }, { "country": "US", "zip": null }]
+    })))
+    .unwrap();
+    assert_eq!(credential.addresses[1].country, "US");
+
+    let error = deserialize_object::<Credential>(object(json!({
+      "given_name": "Erika",
+      "addresses": [{ "country": "DE" }, { "country": "US", "zip": "unknown" }]
+    })))
+    .unwrap_err();
+    assert!(matches!(error, Error::DataTypeMismatch { path, .. } if path == "/addresses/1/zip"));
+
+    let error = deserialize_object::<Credential>(object(json!({ "addresses": [] }))).unwrap_err();
+    assert!(matches!(error, Error::DataTypeMismatch { path, .. } if path.is_empty()));
+  }
+}
@@ -0,0 +1,558 @@
+// Copyright 2020-2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use serde::Deserialize;
+use serde::Serialize;
+use serde_json::Map;
+use serde_json::Value;
+
+use crate::DecodingContext;
+use crate::DecodingOptions;
+use crate::Error;
+use crate::Hasher;
+use crate::KeyBindingJwtClaims;
+use crate::NonceStore;
+use crate::Result;
+use crate::SdJwt;
+use crate::SpecVersion;
+use crate::UnusedDisclosurePolicy;
+
+/// Options of [`SdJwt::validate_full`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ValidationOptions {
+  /// Current time in seconds since the Unix epoch, used to check `exp`, `nbf` and `iat`.
+  pub now: i64,
+  /// Accepted `alg` headers of the issuer-signed and key binding JWT, any algorithm if empty.
+  pub allowed_algs: Vec<String>,
+  /// Whether a key binding JWT is required even if the SD-JWT has no `cnf` claim.
+  pub key_binding_required: bool,
+  /// Expected `aud` of the key binding JWT, not checked if `None`.
+  pub audience: Option<String>,
+  /// Expected `nonce` of the key binding JWT, not checked if `None`.
+  pub nonce: Option<String>,
+  /// Options of resolving the disclosures, e.g. the maximum number of decoys. Unused disclosures are reported
+  /// as warnings with [`UnusedDisclosurePolicy::Warn`].
+  pub decoding: DecodingOptions,
+  /// The draft the SD-JWT is validated against, see [`SdJwt::validate_spec_version`]. It also selects the
+  /// claim of the key binding JWT holding [`SdJwt::sd_hash`], see [`SpecVersion::sd_hash_claim`].
+  pub spec_version: SpecVersion,
+}
+
+/// The group of checks a [`ValidationFinding`] belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum ValidationCheck {
+  /// Signatures of the issuer-signed and key binding JWT.
+  Signature,
+  /// Serialization of the JWTs and resolution of the disclosures.
+  Structure,
+  /// `exp`, `nbf` and `iat` of the issuer-signed and key binding JWT.
+  Time,
+  /// Presence and claims of the key binding JWT.
+  KeyBinding,
+  /// Coherence of the registered claims, see [`validate_claims`](crate::validate_claims).
+  Claims,
+}
+
+/// Whether a [`ValidationFinding`] makes the SD-JWT invalid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+  /// The SD-JWT must be rejected.
+  Error,
+  /// The SD-JWT is valid, but the finding should be looked into.
+  Warning,
+}
+
+/// A failed check of [`SdJwt::validate_full`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValidationFinding {
+  /// The group of the failed check.
+  pub check: ValidationCheck,
+  /// Whether the finding makes the SD-JWT invalid.
+  pub severity: Severity,
+  /// Description of the finding.
+  pub message: String,
+}
+
+/// Result of [`SdJwt::validate_full`], listing every finding in the order the checks were run.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValidationReport {
+  /// All findings, errors and warnings.
+  pub findings: Vec<ValidationFinding>,
+}
+
+impl ValidationReport {
+  /// Returns whether the report contains no [`Severity::Error`].
+  pub fn is_valid(&self) -> bool {
+    self.errors().next().is_none()
+  }
+
+  /// Returns the findings of [`Severity::Error`].
+  pub fn errors(&self) -> impl Iterator<Item = &ValidationFinding> {
+    self
+      .findings
+      .iter()
+      .filter(|finding| finding.severity == Severity::Error)
+  }
+
+  /// Returns the findings of [`Severity::Warning`].
+  pub fn warnings(&self) -> impl Iterator<Item = &ValidationFinding> {
+    self
+      .findings
+      .iter()
+      .filter(|finding| finding.severity == Severity::Warning)
+  }
+
+  pub(crate) fn push(&mut self, check: ValidationCheck, severity: Severity, message: impl Into<String>) {
+    self.findings.push(ValidationFinding {
+      check,
+      severity,
+      message: message.into(),
+    });
+  }
+
+  fn check_times(&mut self, jwt: &str, claims: &Map<String, Value>, now: i64) {
+    // NumericDates may be fractional, see RFC 7519 section 2.
+    let time = |claim: &str| claims.get(claim).and_then(Value::as_f64);
+    let now = now as f64;
+    if let Some(exp) = time("exp").filter(|exp| *exp <= now) {
+      self.push(
+        ValidationCheck::Time,
+        Severity::Error,
+        format!("{} expired at {}", jwt, exp),
+      );
+    }
+    if let Some(nbf) = time("nbf").filter(|nbf| *nbf > now) {
+      self.push(
+        ValidationCheck::Time,
+        Severity::Error,
+        format!("{} is not valid before {}", jwt, nbf),
+      );
+    }
+    if let Some(iat) = time("iat").filter(|iat| *iat > now) {
+      self.push(
+        ValidationCheck::Time,
+        Severity::Warning,
+        format!("{} is issued in the future at {}", jwt, iat),
+      );
+    }
+  }
+}
+
+/// The validity period of an issuer-signed JWT given by its `nbf` and `exp` claims, see
+/// [`SdJwt::validity_window`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValidityWindow {
+  /// `nbf` in seconds since the Unix epoch, rounded up, valid from the beginning if `None`.
+  pub not_before: Option<i64>,
+  /// `exp` in seconds since the Unix epoch, rounded up, valid indefinitely if `None`.
+  pub expires_at: Option<i64>,
+}
+
+impl ValidityWindow {
+  /// Returns whether the window has ended at `now`, using the same comparison as [`SdJwt::validate_full`].
+  pub fn is_expired(&self, now: i64) -> bool {
+    self.expires_at.map_or(false, |exp| exp <= now)
+  }
+
+  /// Returns whether the window has not yet started at `now`.
+  pub fn is_not_yet_valid(&self, now: i64) -> bool {
+    self.not_before.map_or(false, |nbf| nbf > now)
+  }
+
+  /// Returns whether `now` lies in the window.
+  pub fn contains(&self, now: i64) -> bool {
+    !self.is_expired(now) && !self.is_not_yet_valid(now)
+  }
+}
+
+impl SdJwt {
+  /// Returns the validity period of the issuer-signed JWT, e.g. for holders to prune expired credentials.
+  ///
+  /// ## Warning
+  /// The signature of the JWT is not verified.
+  ///
+  /// ## Error
+  /// Same as [`SdJwt::parse_jwt`].
+  pub fn validity_window(&self) -> Result<ValidityWindow> {
+    let claims = self.parse_jwt()?.into_claims();
+    // Fractional NumericDates are rounded up, so that the window agrees with `validate_full` for every `now`.
+    let time = |claim: &str| claims.get(claim).and_then(Value::as_f64).map(|time| time.ceil() as i64);
+    Ok(ValidityWindow {
+      not_before: time("nbf"),
+      expires_at: time("exp"),
+    })
+  }
+
+  /// Returns whether the issuer-signed JWT has expired at `now`, see [`ValidityWindow::is_expired`].
+  ///
+  /// ## Error
+  /// Same as [`SdJwt::parse_jwt`].
+  pub fn is_expired(&self, now: i64) -> Result<bool> {
+    self.validity_window().map(|window| window.is_expired(now))
+  }
+
+  /// Returns whether the issuer-signed JWT is not yet valid at `now`, see [`ValidityWindow::is_not_yet_valid`].
+  ///
+  /// ## Error
+  /// Same as [`SdJwt::parse_jwt`].
+  pub fn is_not_yet_valid(&self, now: i64) -> Result<bool> {
+    self.validity_window().map(|window| window.is_not_yet_valid(now))
+  }
+
+  /// Runs all checks on this SD-JWT and reports every failure and warning instead of stopping at the first
+  /// error, e.g. for conformance testing.
+  ///
+  /// Checks the structure of the JWTs and disclosures with `hasher`, the allowed algorithms, the validity
+  /// period, the key binding JWT and the coherence of the registered claims.
+  ///
+  /// ## Warning
+  /// Signatures are not verified by this crate, which the report always states as a warning. The report
+  /// is no substitute for verifying both JWTs.
+  pub fn validate_full(&self, hasher: &dyn Hasher, options: &ValidationOptions) -> ValidationReport {
+    self.validate_full_with_nonce_store(hasher, options, None)
+  }
+
+  /// Same as [`SdJwt::validate_full`], but also consumes the `nonce` of the key binding JWT from
+  /// `nonce_store` if given, reporting replayed key binding JWTs as errors.
+  ///
+  /// ## Note
+  /// The nonce is consumed even if other checks fail, so a nonce can not be retried with a fixed presentation.
+  pub fn validate_full_with_nonce_store(
+    &self,
+    hasher: &dyn Hasher,
+    options: &ValidationOptions,
+    nonce_store: Option<&dyn NonceStore>,
+  ) -> ValidationReport {
+    let mut report = ValidationReport::default();
+    report.push(
+      ValidationCheck::Signature,
+      Severity::Warning,
+      "signatures are not verified",
+    );
+
+    if !options.allowed_algs.is_empty() {
+      let allowed: Vec<&str> = options.allowed_algs.iter().map(String::as_str).collect();
+      if let Err(error) = self.validate_alg(&allowed) {
+        report.push(ValidationCheck::Structure, Severity::Error, error.to_string());
+      }
+    }
+
+    let claims = match self.parse_jwt() {
+      Ok(jwt) => jwt.into_claims(),
+      Err(error) => {
+        report.push(ValidationCheck::Structure, Severity::Error, error.to_string());
+        return report;
+      }
+    };
+    if let Err(error) = self.validate_spec_version(options.spec_version) {
+      report.push(ValidationCheck::Structure, Severity::Error, error.to_string());
+    }
+    match self.unused_disclosures(DecodingContext::new(hasher, options.decoding)) {
+      Err(error) => report.push(ValidationCheck::Structure, Severity::Error, error.to_string()),
+      Ok(unused) if !unused.is_empty() && options.decoding.unused_disclosure_policy == UnusedDisclosurePolicy::Warn => {
+        report.push(
+          ValidationCheck::Structure,
+          Severity::Warning,
+          format!("{} disclosures do not match any digest", unused.len()),
+        )
+      }
+      Ok(_) => (),
+    }
+    report.check_times("issuer-signed JWT", &claims, options.now);
+
+    let key_binding_required = options.key_binding_required || claims.contains_key("cnf");
+    match self.parse_key_binding_jwt() {
+      Err(error) => report.push(ValidationCheck::KeyBinding, Severity::Error, error.to_string()),
+      Ok(None) if key_binding_required => report.push(
+        ValidationCheck::KeyBinding,
+        Severity::Error,
+        "key binding JWT is required but missing",
+      ),
+      Ok(None) => (),
+      Ok(Some(jwt)) => {
+        if !claims.contains_key("cnf") {
+          report.push(
+            ValidationCheck::KeyBinding,
+            Severity::Warning,
+            "key binding JWT is present but the SD-JWT has no `cnf` claim",
+          );
+        }
+        if jwt.typ() != Some(KeyBindingJwtClaims::KB_JWT_HEADER_TYP) {
+          report.push(
+            ValidationCheck::KeyBinding,
+            Severity::Error,
+            format!(
+              "expected typ {}, found {:?}",
+              KeyBindingJwtClaims::KB_JWT_HEADER_TYP,
+              jwt.typ()
+            ),
+          );
+        }
+        let kb_claims = jwt.into_claims();
+        let sd_hash_claim = options.spec_version.sd_hash_claim();
+        let sd_hash = kb_claims.get(sd_hash_claim).and_then(Value::as_str);
+        if !sd_hash.map_or(false, |sd_hash| crate::digests_equal(sd_hash, &self.sd_hash(hasher))) {
+          report.push(
+            ValidationCheck::KeyBinding,
+            Severity::Error,
+            format!("{} is missing or does not match the SD-JWT", sd_hash_claim),
+          );
+        }
+        for (claim, expected) in [("aud", &options.audience), ("nonce", &options.nonce)] {
+          let Some(expected) = expected else { continue };
+          if kb_claims.get(claim).and_then(Value::as_str) != Some(expected.as_str()) {
+            report.push(
+              ValidationCheck::KeyBinding,
+              Severity::Error,
+              format!("{} of the key binding JWT is not {}", claim, expected),
+            );
+          }
+        }
+        if let Some(store) = nonce_store {
+          let nonce = kb_claims.get("nonce").and_then(Value::as_str).unwrap_or_default();
+          if !store.consume(nonce, options.now) {
+            report.push(
+              ValidationCheck::KeyBinding,
+              Severity::Error,
+              Error::InvalidNonce(nonce.to_owned()).to_string(),
+            );
+          }
+        }
+        report.check_times("key binding JWT", &kb_claims, options.now);
+      }
+    }
+
+    for violation in crate::validate_claims(&claims, false) {
+      report.push(
+        ValidationCheck::Claims,
+        Severity::Error,
+        format!("`{}`: {}", violation.path, violation.reason),
+      );
+    }
+    report
+  }
+}
+
+#[cfg(all(test, feature = "sha"))]
+mod test {
+  use super::Severity;
+  use super::ValidationCheck;
+  use super::ValidationOptions;
+  use super::ValidityWindow;
+  use crate::test_utils;
+  use crate::InMemoryNonceStore;
+  use crate::KeyBindingJwtClaims;
+  use crate::NonceStore;
+  use crate::SdJwt;
+  use crate::SdObjectEncoder;
+  use crate::Sha256Hasher;
+  use crate::SpecVersion;
+  use serde_json::json;
+
+  #[test]
+  fn validate_full() {
+    let hasher = Sha256Hasher::new();
+    let mut encoder = SdObjectEncoder::try_from(json!({
+      "iss": "https://issuer.example.com",
+      "iat": 1000,
+      "exp": 2000,
+      "cnf": { "jkt": "NzbLsXh8uDCcd-6MNwXF4W_7noWXFZAfHkxZsRGC9Xs" },
+      "given_name": "Erika"
+    }))
+    .unwrap();
+    let disclosure = encoder.conceal("/given_name", None).unwrap();
+    let issuer_jwt = test_utils::jwt(json!({ "alg": "ES256" }), encoder.try_to_string().unwrap());
+    let kb_claims = KeyBindingJwtClaims::new(
+      &hasher,
+      issuer_jwt.clone(),
+      vec![disclosure.to_string()],
+      "nonce".to_string(),
+      "https://verifier.example.org".to_string(),
+      1500,
+    );
+    let kb_jwt = test_utils::jwt(
+      json!({ "alg": "ES256", "typ": "kb+jwt" }),
+      serde_json::to_string(&kb_claims).unwrap(),
+    );
+    let options = ValidationOptions {
+      now: 1500,
+      allowed_algs: vec!["ES256".to_string()],
+      nonce: Some("nonce".to_string()),
+      ..Default::default()
+    };
+
+    let sd_jwt = SdJwt::new(issuer_jwt.clone(), vec![disclosure.to_string()], Some(kb_jwt));
+    let report = sd_jwt.validate_full(&hasher, &options);
+    assert!(report.is_valid(), "{:?}", report);
+    assert_eq!(report.warnings().next().unwrap().check, ValidationCheck::Signature);
+
+    // Every failure is reported: expired, missing key binding and an unused disclosure.
+    let sd_jwt = SdJwt::new(issuer_jwt, vec![disclosure.to_string(), disclosure.to_string()], None);
+    let options = ValidationOptions { now: 3000, ..options };
+    let report = sd_jwt.validate_full(&hasher, &options);
+    let checks: Vec<_> = report.errors().map(|finding| finding.check).collect();
+    assert_eq!(
+      checks,
+      vec![
+        ValidationCheck::Structure,
+        ValidationCheck::Time,
+        ValidationCheck::KeyBinding
+      ]
+    );
+    assert!(report
+      .findings
+      .iter()
+      .any(|finding| finding.severity == Severity::Warning));
+  }
+
+  #[test]
+  fn spec_version() {
+    let hasher = Sha256Hasher::new();
+    let issuer_jwt = test_utils::jwt(json!({ "alg": "ES256", "typ": "vc+sd-jwt" }), "{}");
+    let kb_claims = KeyBindingJwtClaims::new(
+      &hasher,
+      issuer_jwt.clone(),
+      vec![],
+      "nonce".to_string(),
+      "https://verifier.example.org".to_string(),
+      1500,
+    );
+    let kb_jwt = |version: SpecVersion| {
+      test_utils::jwt(
+        json!({ "alg": "ES256", "typ": "kb+jwt" }),
+        json!(kb_claims.to_object_for(version).unwrap()).to_string(),
+      )
+    };
+    let options = |spec_version: SpecVersion| ValidationOptions {
+      now: 1500,
+      spec_version,
+      ..Default::default()
+    };
+
+    for version in [SpecVersion::Draft07, SpecVersion::Draft08] {
+      let sd_jwt = SdJwt::new(issuer_jwt.clone(), vec![], Some(kb_jwt(version)));
+      let report = sd_jwt.validate_full(&hasher, &options(version));
+      assert!(report.is_valid(), "{:?}", report);
+    }
+    // The digest must be in the claim of the selected draft.
+    let sd_jwt = SdJwt::new(issuer_jwt, vec![], Some(kb_jwt(SpecVersion::Draft07)));
+    let report = sd_jwt.validate_full(&hasher, &options(SpecVersion::Draft08));
+    assert_eq!(
+      report.errors().next().unwrap().message,
+      "sd_hash is missing or does not match the SD-JWT"
+    );
+
+    let sd_jwt = SdJwt::new(
+      test_utils::jwt(json!({ "alg": "ES256", "typ": "dc+sd-jwt" }), "{}"),
+      vec![],
+      None,
+    );
+    assert!(sd_jwt.validate_full(&hasher, &options(SpecVersion::Draft08)).is_valid());
+    let report = sd_jwt.validate_full(&hasher, &options(SpecVersion::Draft07));
+    assert_eq!(report.errors().next().unwrap().check, ValidationCheck::Structure);
+  }
+
+  #[test]
+  fn nonce_store() {
+    let hasher = Sha256Hasher::new();
+    let mut encoder = SdObjectEncoder::try_from(json!({ "cnf": { "kid": "holder" } })).unwrap();
+    encoder.add_sd_alg_property();
+    let issuer_jwt = test_utils::jwt(json!({ "alg": "ES256" }), encoder.try_to_string().unwrap());
+    let kb_claims = KeyBindingJwtClaims::new(
+      &hasher,
+      issuer_jwt.clone(),
+      vec![],
+      "nonce".to_string(),
+      "https://verifier.example.org".to_string(),
+      1500,
+    );
+    let kb_jwt = test_utils::jwt(
+      json!({ "alg": "ES256", "typ": "kb+jwt" }),
+      serde_json::to_string(&kb_claims).unwrap(),
+    );
+    let sd_jwt = SdJwt::new(issuer_jwt, vec![], Some(kb_jwt));
+    let options = ValidationOptions {
+      now: 1500,
+      ..Default::default()
+    };
+    let store = InMemoryNonceStore::new();
+    store.issue("nonce", 2000);
+
+    assert!(sd_jwt
+      .validate_full_with_nonce_store(&hasher, &options, Some(&store))
+      .is_valid());
+    // The nonce was consumed, the replayed key binding JWT is rejected.
+    let report = sd_jwt.validate_full_with_nonce_store(&hasher, &options, Some(&store));
+    let finding = report.errors().next().unwrap();
+    assert_eq!(finding.check, ValidationCheck::KeyBinding);
+    assert_eq!(
+      finding.message,
+      "nonce nonce was not issued, has expired or was already used"
+    );
+    assert!(sd_jwt.validate_full(&hasher, &options).is_valid());
+  }
+
+  #[test]
+  fn fractional_times() {
+    let sd_jwt = SdJwt::new(
+      test_utils::jwt(
+        json!({ "alg": "ES256" }),
+        json!({ "iat": 1000.5, "exp": 2000.5 }).to_string(),
+      ),
+      vec![],
+      None,
+    );
+    let hasher = Sha256Hasher::new();
+    let options = ValidationOptions {
+      now: 2000,
+      ..Default::default()
+    };
+    assert!(sd_jwt.validate_full(&hasher, &options).is_valid());
+
+    let report = sd_jwt.validate_full(&hasher, &ValidationOptions { now: 2001, ..options });
+    let finding = report.errors().next().unwrap();
+    assert_eq!(finding.check, ValidationCheck::Time);
+    assert_eq!(finding.message, "issuer-signed JWT expired at 2000.5");
+  }
+
+  #[test]
+  fn validity_window() {
+    let sd_jwt = SdJwt::new(
+      test_utils::jwt(
+        json!({ "alg": "ES256" }),
+        json!({ "nbf": 1000, "exp": 2000 }).to_string(),
+      ),
+      vec![],
+      None,
+    );
+    let window = sd_jwt.validity_window().unwrap();
+    assert_eq!(
+      window,
+      ValidityWindow {
+        not_before: Some(1000),
+        expires_at: Some(2000)
+      }
+    );
+    assert!(sd_jwt.is_not_yet_valid(999).unwrap());
+    assert!(window.contains(1000));
+    assert!(!sd_jwt.is_expired(1999).unwrap());
+    assert!(sd_jwt.is_expired(2000).unwrap());
+    assert!(ValidityWindow::default().contains(i64::MAX));
+
+    let sd_jwt = SdJwt::new(
+      test_utils::jwt(
+        json!({ "alg": "ES256" }),
+        json!({ "nbf": 1000.5, "exp": 2000.5 }).to_string(),
+      ),
+      vec![],
+      None,
+    );
+    assert!(sd_jwt.is_not_yet_valid(1000).unwrap());
+    assert!(!sd_jwt.is_not_yet_valid(1001).unwrap());
+    assert!(!sd_jwt.is_expired(2000).unwrap());
+    assert!(sd_jwt.is_expired(2001).unwrap());
+  }
+}